@@ -0,0 +1,188 @@
+use crate::export::{ExportFormat, GameMode};
+use crate::function::{Config, GridArgs, SolverArgs, WordSetArgs};
+use crate::game::Difficulty;
+use crate::storage::StoreKind;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+/// Wordle in the terminal, with an optional solver.
+///
+/// Without a subcommand the options are those of `play`.
+#[derive(Parser, Debug)]
+#[command(version, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub play: Config,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Play Wordle (the default)
+    Play(Config),
+    /// Enter guesses and their feedback, get remaining words and recommendations
+    Solve(SolveArgs),
+    /// Print statistics from a state file without playing
+    Stats(StatsArgs),
+    /// Show a past game from a state file
+    Replay(ReplayArgs),
+    /// Compare the players in a state file by win rate and average guesses
+    Leaderboard(LeaderboardArgs),
+    /// Write the game history as CSV or JSON Lines
+    Export(ExportArgs),
+    /// Print the spoiler-free emoji grid of a past game
+    Share(ShareArgs),
+    /// Check pasted emoji grids against the answer and list possible guesses
+    Verify(VerifyArgs),
+    /// Query and validate word lists
+    Words(WordsArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SolveArgs {
+    #[command(flatten)]
+    pub words: WordSetArgs,
+    /// Consider every acceptable word as a possible answer, not only the
+    /// final list; for puzzles whose answers are unknown
+    #[arg(long)]
+    pub all_answers: bool,
+    #[command(flatten)]
+    pub solver: SolverArgs,
+}
+
+/// The state file a subcommand reads games from.
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    /// File with the game history
+    #[arg(short = 'S', long, value_name = "PATH")]
+    pub state: String,
+    /// Storage format of the state file, instead of guessing from its extension
+    #[arg(long, value_name = "KIND")]
+    pub store: Option<StoreKind>,
+}
+
+/// The games of one profile in a state file.
+#[derive(Args, Debug)]
+pub struct PlayerHistoryArgs {
+    #[command(flatten)]
+    pub history: HistoryArgs,
+    /// Profile whose games to use; the default profile if not given
+    #[arg(short = 'p', long, value_name = "NAME")]
+    pub player: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    #[command(flatten)]
+    pub games: PlayerHistoryArgs,
+    /// Print the statistics as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    #[command(flatten)]
+    pub games: PlayerHistoryArgs,
+    /// Game to show, 1 is the oldest; negative numbers count from the newest
+    #[arg(short, long, default_value_t = -1, allow_negative_numbers = true)]
+    pub game: i64,
+}
+
+#[derive(Args, Debug)]
+pub struct ShareArgs {
+    #[command(flatten)]
+    pub games: PlayerHistoryArgs,
+    /// Game to share, 1 is the oldest; negative numbers count from the newest
+    #[arg(short, long, default_value_t = -1, allow_negative_numbers = true)]
+    pub game: i64,
+    #[command(flatten)]
+    pub grid: GridArgs,
+    /// File to write the grid to instead of standard output
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    #[command(flatten)]
+    pub words: WordSetArgs,
+    /// Answer of the grids; by default taken from the puzzle number in each header
+    #[arg(long, value_name = "WORD")]
+    pub answer: Option<String>,
+    /// Daily puzzle the grids are from, overriding their headers
+    #[arg(long, value_name = "N", conflicts_with = "answer")]
+    pub puzzle: Option<u32>,
+    /// Candidate guesses to show per row, 0 for all
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub limit: usize,
+    /// File with the pasted grids; standard input if not given
+    pub input: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct LeaderboardArgs {
+    #[command(flatten)]
+    pub history: HistoryArgs,
+    /// Print the leaderboard as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    pub history: HistoryArgs,
+    /// Output format
+    #[arg(long, value_name = "FORMAT", default_value = "csv")]
+    pub format: ExportFormat,
+    /// One row per guess instead of one row per game
+    #[arg(long)]
+    pub guesses: bool,
+    /// Only games started on or after this day
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub from: Option<String>,
+    /// Only games started on or before this day
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub to: Option<String>,
+    /// Only games of this mode
+    #[arg(long)]
+    pub mode: Option<GameMode>,
+    /// Only games played at this difficulty
+    #[arg(long, value_name = "LEVEL")]
+    pub difficulty: Option<Difficulty>,
+    /// Only games of this profile; every profile if not given
+    #[arg(short = 'p', long, value_name = "NAME")]
+    pub player: Option<String>,
+    /// File to write to instead of standard output
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum WordList {
+    Final,
+    Acceptable,
+}
+
+#[derive(Args, Debug)]
+pub struct WordsArgs {
+    #[command(flatten)]
+    pub words: WordSetArgs,
+    /// Print every word of a list
+    #[arg(long, value_name = "LIST")]
+    pub list: Option<WordList>,
+    /// Words to look up in the lists
+    pub query: Vec<String>,
+}
+
+impl Cli {
+    pub fn from_args() -> Cli {
+        // `-so` predates the subcommands and cannot be expressed as a short flag
+        let args = std::env::args().map(|arg| match arg.as_str() {
+            "-so" | "--solver-only" => "solve".to_string(),
+            _ => arg,
+        });
+        Cli::parse_from(args)
+    }
+}
@@ -0,0 +1,38 @@
+use crate::function;
+use chrono::{Local, NaiveDate};
+use std::error::Error;
+
+/// Date of puzzle #0, the first day of the official sequence.
+pub fn first_puzzle_date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2021, 6, 19).expect("valid date")
+}
+
+/// Fixed shuffle seed so everyone gets the same word for the same date.
+pub const DAILY_SEED: u64 = 20210619;
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Parses a `YYYY-MM-DD` date.
+pub fn parse_date(s: &str) -> Result<NaiveDate, Box<dyn Error>> {
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("invalid date '{}': {e}, expected YYYY-MM-DD", s.trim()).into())
+}
+
+/// Number of days between puzzle #0 and `date`.
+pub fn puzzle_number(date: NaiveDate) -> Result<u32, Box<dyn Error>> {
+    let first = first_puzzle_date();
+    let days = (date - first).num_days();
+    if days < 0 {
+        return Err(format!("no daily puzzle before {first}").into());
+    }
+    Ok(days as u32)
+}
+
+/// Maps a puzzle number to its answer, cycling through `final_words` once
+/// every word has been used.
+pub fn answer_for_puzzle(puzzle: u32, final_words: &[String]) -> String {
+    let day = puzzle as usize % final_words.len() + 1;
+    function::get_answer_for_day(day as u32, DAILY_SEED, final_words)
+}
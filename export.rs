@@ -0,0 +1,189 @@
+//! Game history as CSV or JSON Lines, for spreadsheets and notebooks.
+
+use crate::function::{self, GameRecord};
+use crate::game::{Difficulty, GameStatus};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::error::Error;
+use std::io::Write;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+}
+
+/// How the answer of a game was chosen.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GameMode {
+    /// `--daily` puzzles
+    Daily,
+    /// `--day`/`--seed` answer sequences
+    Seeded,
+    /// Everything else: `--word`, `--random` or typed-in answers
+    Free,
+}
+
+impl GameMode {
+    pub fn of(record: &GameRecord) -> Self {
+        if record.puzzle.is_some() {
+            GameMode::Daily
+        } else if record.day.is_some() || record.seed.is_some() {
+            GameMode::Seeded
+        } else {
+            GameMode::Free
+        }
+    }
+}
+
+/// Which games to export; unset fields match every game.
+#[derive(Debug, Default)]
+pub struct ExportFilter {
+    /// First and last day (local time) a game may have started on.
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub mode: Option<GameMode>,
+    pub difficulty: Option<Difficulty>,
+}
+
+impl ExportFilter {
+    /// Games without a start time only match when no date range is given.
+    pub fn matches(&self, record: &GameRecord) -> bool {
+        if self.from.is_some() || self.to.is_some() {
+            let Some(started_at) = record.started_at else {
+                return false;
+            };
+            let date = started_at.with_timezone(&Local).date_naive();
+            if self.from.is_some_and(|from| date < from) || self.to.is_some_and(|to| date > to) {
+                return false;
+            }
+        }
+        self.mode.is_none_or(|mode| GameMode::of(record) == mode)
+            && self
+                .difficulty
+                .is_none_or(|difficulty| record.difficulty.unwrap_or_default() == difficulty)
+    }
+}
+
+/// One game. Guesses and their feedback are space-separated so the row
+/// stays flat for CSV.
+#[derive(Serialize)]
+struct GameRow<'a> {
+    /// Position in the state file, 1 is the oldest game.
+    game: usize,
+    player: &'a str,
+    answer: &'a str,
+    mode: GameMode,
+    difficulty: Difficulty,
+    outcome: Option<GameStatus>,
+    attempts: usize,
+    max_guesses: usize,
+    guesses: String,
+    feedback: String,
+    puzzle: Option<u32>,
+    day: Option<u32>,
+    seed: Option<u64>,
+    word_list: Option<&'a str>,
+    started_at: Option<DateTime<Utc>>,
+    finished_at: Option<DateTime<Utc>>,
+}
+
+/// One guess of a game, `position` counting from 1.
+#[derive(Serialize)]
+struct GuessRow<'a> {
+    game: usize,
+    player: &'a str,
+    answer: &'a str,
+    position: usize,
+    guess: &'a str,
+    feedback: String,
+}
+
+fn feedback_of(record: &GameRecord, guess: &str) -> String {
+    function::color_state(&guess.to_lowercase(), &record.answer.to_lowercase()).to_string()
+}
+
+/// Writes `games`, numbered as in the state file, one row per game or with
+/// `per_guess` one row per guess.
+pub fn write_games(
+    out: impl Write,
+    format: ExportFormat,
+    games: &[(usize, &GameRecord)],
+    per_guess: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut sink = RowSink::new(out, format);
+    for &(game, record) in games {
+        if per_guess {
+            for (i, guess) in record.guesses.iter().enumerate() {
+                sink.write(&GuessRow {
+                    game,
+                    player: record.player_name(),
+                    answer: &record.answer,
+                    position: i + 1,
+                    guess,
+                    feedback: feedback_of(record, guess),
+                })?;
+            }
+        } else {
+            let feedback: Vec<String> = record
+                .guesses
+                .iter()
+                .map(|guess| feedback_of(record, guess))
+                .collect();
+            sink.write(&GameRow {
+                game,
+                player: record.player_name(),
+                answer: &record.answer,
+                mode: GameMode::of(record),
+                difficulty: record.difficulty.unwrap_or_default(),
+                outcome: record.outcome,
+                attempts: record.guesses.len(),
+                max_guesses: record.max_guesses,
+                guesses: record.guesses.join(" "),
+                feedback: feedback.join(" "),
+                puzzle: record.puzzle,
+                day: record.day,
+                seed: record.seed,
+                word_list: record.word_list.as_deref(),
+                started_at: record.started_at,
+                finished_at: record.finished_at,
+            })?;
+        }
+    }
+    sink.finish()
+}
+
+enum RowSink<W: Write> {
+    Csv(Box<csv::Writer<W>>),
+    Jsonl(W),
+}
+
+impl<W: Write> RowSink<W> {
+    fn new(out: W, format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Csv => RowSink::Csv(Box::new(csv::Writer::from_writer(out))),
+            ExportFormat::Jsonl => RowSink::Jsonl(out),
+        }
+    }
+
+    fn write(&mut self, row: &impl Serialize) -> Result<(), Box<dyn Error>> {
+        match self {
+            RowSink::Csv(writer) => writer.serialize(row)?,
+            RowSink::Jsonl(out) => {
+                serde_json::to_writer(&mut *out, row)?;
+                writeln!(out)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), Box<dyn Error>> {
+        match self {
+            RowSink::Csv(mut writer) => writer.flush()?,
+            RowSink::Jsonl(mut out) => out.flush()?,
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Colour of a single letter after a guess, also used for the keyboard.
+///
+/// The variants are ordered by how much they reveal, so the keyboard can
+/// keep the maximum state seen for each letter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LetterState {
+    /// Not in the answer (R).
+    Absent,
+    /// In the answer but at another position (Y).
+    Present,
+    /// In the answer at this position (G).
+    Correct,
+}
+
+impl LetterState {
+    pub fn as_char(self) -> char {
+        match self {
+            LetterState::Absent => 'R',
+            LetterState::Present => 'Y',
+            LetterState::Correct => 'G',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'R' => Some(LetterState::Absent),
+            'Y' => Some(LetterState::Present),
+            'G' => Some(LetterState::Correct),
+            _ => None,
+        }
+    }
+
+    fn digit(self) -> u8 {
+        match self {
+            LetterState::Absent => 0,
+            LetterState::Present => 1,
+            LetterState::Correct => 2,
+        }
+    }
+
+    fn from_digit(digit: u8) -> Self {
+        match digit {
+            0 => LetterState::Absent,
+            1 => LetterState::Present,
+            _ => LetterState::Correct,
+        }
+    }
+}
+
+/// Longest word the game supports.
+pub const MAX_WORD_LENGTH: usize = 10;
+
+/// Per-letter result of scoring one guess against the answer.
+///
+/// Stored inline with a length so it stays `Copy` for any word length up to
+/// [`MAX_WORD_LENGTH`]; unused slots are always `Absent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Feedback {
+    len: u8,
+    states: [LetterState; MAX_WORD_LENGTH],
+}
+
+impl Feedback {
+    /// Panics if `states` is longer than [`MAX_WORD_LENGTH`].
+    pub fn new(states: &[LetterState]) -> Self {
+        assert!(states.len() <= MAX_WORD_LENGTH, "word too long");
+        let mut feedback = Feedback::absent(states.len());
+        feedback.states[..states.len()].copy_from_slice(states);
+        feedback
+    }
+
+    fn absent(len: usize) -> Self {
+        Feedback {
+            len: len as u8,
+            states: [LetterState::Absent; MAX_WORD_LENGTH],
+        }
+    }
+
+    /// Number of distinct feedback patterns for words of `word_length`, i.e. `3^n`.
+    pub fn pattern_count(word_length: usize) -> usize {
+        3usize.pow(word_length as u32)
+    }
+
+    /// Scores `guess` against `answer`, giving greens priority over yellows
+    /// so repeated letters are only marked as often as they occur.
+    pub fn compute(guess: &str, answer: &str) -> Self {
+        // on the stack, this runs for every cell of the solver's pattern table
+        let mut guess_chars = ['\0'; MAX_WORD_LENGTH];
+        let mut answer_chars = ['\0'; MAX_WORD_LENGTH];
+        let guess_len = copy_chars(guess, &mut guess_chars);
+        let answer_len = copy_chars(answer, &mut answer_chars);
+        let len = guess_len.min(answer_len);
+        let mut feedback = Feedback::absent(len);
+        let state = &mut feedback.states[..len];
+
+        let mut answer_counts = [0; 26];
+        for c in answer.chars() {
+            if let Some(index) = letter_index(c) {
+                answer_counts[index] += 1;
+            }
+        }
+        for i in 0..len {
+            if guess_chars[i] == answer_chars[i] {
+                state[i] = LetterState::Correct;
+                if let Some(index) = letter_index(guess_chars[i]) {
+                    answer_counts[index] -= 1;
+                }
+            }
+        }
+        for i in 0..len {
+            if state[i] != LetterState::Correct
+                && let Some(index) = letter_index(guess_chars[i])
+                && answer_counts[index] > 0
+            {
+                state[i] = LetterState::Present;
+                answer_counts[index] -= 1;
+            }
+        }
+        feedback
+    }
+
+    pub fn states(&self) -> &[LetterState] {
+        &self.states[..self.len as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_win(&self) -> bool {
+        self.states().iter().all(|&s| s == LetterState::Correct)
+    }
+
+    /// Encodes the pattern as a base-3 number in `0..pattern_count(len)`,
+    /// first letter most significant. Handy as a compact hash key.
+    pub fn index(&self) -> u32 {
+        self.states()
+            .iter()
+            .fold(0, |acc, s| acc * 3 + s.digit() as u32)
+    }
+
+    pub fn from_index(mut index: u32, word_length: usize) -> Self {
+        let mut feedback = Feedback::absent(word_length);
+        for slot in feedback.states[..word_length].iter_mut().rev() {
+            *slot = LetterState::from_digit((index % 3) as u8);
+            index /= 3;
+        }
+        feedback
+    }
+}
+
+/// Position of `c` in the alphabet; other characters are never yellow.
+fn letter_index(c: char) -> Option<usize> {
+    c.is_ascii_alphabetic()
+        .then(|| (c.to_ascii_uppercase() as u8 - b'A') as usize)
+}
+
+/// Copies the first characters of `word` into `buffer`, returning how many.
+fn copy_chars(word: &str, buffer: &mut [char; MAX_WORD_LENGTH]) -> usize {
+    let mut len = 0;
+    for (slot, c) in buffer.iter_mut().zip(word.chars()) {
+        *slot = c;
+        len += 1;
+    }
+    len
+}
+
+impl fmt::Display for Feedback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for state in self.states() {
+            write!(f, "{}", state.as_char())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseFeedbackError {
+    /// Empty or longer than [`MAX_WORD_LENGTH`].
+    WrongLength(usize),
+    InvalidChar(char),
+}
+
+impl fmt::Display for ParseFeedbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseFeedbackError::WrongLength(len) => {
+                write!(
+                    f,
+                    "feedback must be 1 to {MAX_WORD_LENGTH} characters long, got {len}"
+                )
+            }
+            ParseFeedbackError::InvalidChar(c) => {
+                write!(
+                    f,
+                    "invalid feedback character '{c}', use only 'G', 'Y', 'R'"
+                )
+            }
+        }
+    }
+}
+
+impl Error for ParseFeedbackError {}
+
+impl FromStr for Feedback {
+    type Err = ParseFeedbackError;
+
+    /// Parses the `G`/`Y`/`R` text protocol, case-insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.trim().chars().collect();
+        if chars.is_empty() || chars.len() > MAX_WORD_LENGTH {
+            return Err(ParseFeedbackError::WrongLength(chars.len()));
+        }
+        let mut feedback = Feedback::absent(chars.len());
+        for (slot, &c) in feedback.states.iter_mut().zip(&chars) {
+            *slot = LetterState::from_char(c).ok_or(ParseFeedbackError::InvalidChar(c))?;
+        }
+        Ok(feedback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn feedback(text: &str) -> Feedback {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn compute_accepts_any_characters() {
+        assert_eq!(Feedback::compute("cafés", "cafes"), feedback("GGGRG"));
+        assert_eq!(Feedback::compute("ab{de", "{abde"), feedback("YYRGG"));
+        assert_eq!(Feedback::compute("éé", "ée"), feedback("GR"));
+    }
+
+    #[test]
+    fn compute_marks_each_repeated_letter_once() {
+        // one E in the answer: only the first E of the guess is yellow
+        assert_eq!(Feedback::compute("eerie", "baker"), feedback("YRYRR"));
+        // greens claim their letter before yellows do
+        assert_eq!(Feedback::compute("eerie", "geese"), feedback("YGRRG"));
+        assert_eq!(Feedback::compute("geese", "eerie"), feedback("RGYRG"));
+    }
+
+    #[test]
+    fn index_round_trips_for_every_length() {
+        for word_length in 1..=MAX_WORD_LENGTH {
+            for index in 0..Feedback::pattern_count(word_length) as u32 {
+                let feedback = Feedback::from_index(index, word_length);
+                assert_eq!(feedback.len(), word_length);
+                assert_eq!(feedback.index(), index);
+                assert_eq!(feedback.to_string().parse::<Feedback>(), Ok(feedback));
+            }
+        }
+    }
+
+    #[test]
+    fn parse_rejects_bad_feedback() {
+        assert_eq!(
+            "".parse::<Feedback>(),
+            Err(ParseFeedbackError::WrongLength(0))
+        );
+        assert_eq!(
+            "GGGGGGGGGGG".parse::<Feedback>(),
+            Err(ParseFeedbackError::WrongLength(11))
+        );
+        assert_eq!(
+            "GGXGG".parse::<Feedback>(),
+            Err(ParseFeedbackError::InvalidChar('X'))
+        );
+        assert_eq!("gyRrg".parse::<Feedback>(), Ok(feedback("GYRRG")));
+    }
+}
@@ -1,4 +1,11 @@
 use crate::builtin_words;
+use crate::feedback::{Feedback, LetterState, MAX_WORD_LENGTH};
+use crate::game::{self, Difficulty, GameStatus, GuessError};
+use crate::migrate;
+use crate::storage::StoreKind;
+use crate::strategy::StrategyKind;
+use chrono::{DateTime, Utc};
+use clap::Args;
 use console::{self, style};
 use rand::SeedableRng;
 use rand::rngs::StdRng;
@@ -7,52 +14,397 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::error::Error;
-use std::fs;
+use std::fs::{self, TryLockError};
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GameState {
+    /// See [`migrate`] for how older files are upgraded.
+    #[serde(default = "current_schema_version")]
+    pub schema_version: u32,
     #[serde(default)]
     pub total_rounds: u32,
     #[serde(default)]
     pub games: Vec<GameRecord>,
+    /// Games left before finishing, at most one per player, saved after
+    /// every guess.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub in_progress: Vec<GameRecord>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+fn current_schema_version() -> u32 {
+    migrate::CURRENT_SCHEMA_VERSION
+}
+
+impl GameState {
+    pub fn new(games: Vec<GameRecord>) -> Self {
+        GameState {
+            schema_version: migrate::CURRENT_SCHEMA_VERSION,
+            total_rounds: games.len() as u32,
+            games,
+            in_progress: Vec::new(),
+        }
+    }
+
+    /// The unfinished game of `player`, see [`GameRecord::player`].
+    pub fn in_progress_of(&self, player: Option<&str>) -> Option<&GameRecord> {
+        self.in_progress
+            .iter()
+            .find(|record| record.player.as_deref() == player)
+    }
+}
+
+/// One finished game as stored in the state file.
+///
+/// Everything after `max_guesses` was added later and is missing from older
+/// state files, hence the `Option`s.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct GameRecord {
     #[serde(default)]
     pub answer: String,
     #[serde(default)]
     pub guesses: Vec<String>,
+    #[serde(default = "default_max_guesses")]
+    pub max_guesses: usize,
+    /// Daily puzzle number, only set for `--daily` games.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub puzzle: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<Difficulty>,
+    /// Day and seed of the answer sequence, for `--day`/`--seed` games.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Which final word list was active, see [`word_list_id`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_list: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<GameStatus>,
+    /// Profile that played the game, `None` for [`DEFAULT_PLAYER`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player: Option<String>,
+}
+
+fn default_max_guesses() -> usize {
+    game::DEFAULT_MAX_ATTEMPTS
+}
+
+impl GameRecord {
+    pub fn player_name(&self) -> &str {
+        self.player.as_deref().unwrap_or(DEFAULT_PLAYER)
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+/// Profile of games played without `--player`.
+pub const DEFAULT_PLAYER: &str = "default";
+
+/// Normalizes a `--player` name as stored in [`GameRecord::player`]:
+/// surrounding spaces are dropped and the default profile becomes `None`.
+pub fn player_key(name: Option<&str>) -> Result<Option<String>, Box<dyn Error>> {
+    match name.map(str::trim) {
+        None => Ok(None),
+        Some("") => Err("player name must not be empty".into()),
+        Some(DEFAULT_PLAYER) => Ok(None),
+        Some(name) => Ok(Some(name.to_string())),
+    }
+}
+
+/// The games of one profile, in the order they were played.
+pub fn player_games(games: Vec<GameRecord>, player: Option<&str>) -> Vec<GameRecord> {
+    games
+        .into_iter()
+        .filter(|record| record.player.as_deref() == player)
+        .collect()
+}
+
+/// Word length of the builtin word lists.
+pub const DEFAULT_WORD_LENGTH: usize = 5;
+
+/// Options for a Wordle session.
+///
+/// The same struct is parsed from the command line and from the JSON file
+/// given by `--config`, so a new option only needs to be declared here.
+/// JSON keys use the field names, e.g. `"final_set"` for `--final-set`.
+#[derive(Args, Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
 pub struct Config {
-    pub random: Option<bool>,
-    pub difficult: Option<bool>,
-    pub stats: Option<bool>,
+    /// Answer word for a single game
+    #[arg(short, long, conflicts_with_all = ["random", "day", "seed", "daily", "date"])]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub word: Option<String>,
+    /// Pick a new random answer from the final word set for each game
+    #[arg(short, long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub random: bool,
+    /// Difficulty; `-D` alone means hard
+    #[arg(
+        short = 'D',
+        long,
+        value_name = "LEVEL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "hard"
+    )]
+    #[serde(
+        deserialize_with = "deserialize_difficulty",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub difficult: Option<Difficulty>,
+    /// Print statistics after each game
+    #[arg(short = 't', long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub stats: bool,
+    /// Starting day of the seeded answer sequence (1-based)
+    #[arg(short, long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub day: Option<u32>,
+    /// Seed for the answer sequence
+    #[arg(short, long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<u64>,
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub words: WordSetArgs,
+    /// File to load and save game history; `.db`/`.sqlite` files use SQLite
+    #[arg(short = 'S', long, value_name = "PATH")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// Storage format of the state file, instead of guessing from its extension
+    #[arg(long, value_name = "KIND")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<StoreKind>,
+    /// Number of guesses per game
+    #[arg(short, long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_guesses: Option<usize>,
+    /// Play today's daily puzzle
+    #[arg(long, conflicts_with_all = ["random", "day", "seed"])]
+    #[serde(skip_serializing_if = "is_false")]
+    pub daily: bool,
+    /// Play the daily puzzle of a date instead of today (implies --daily)
+    #[arg(long, value_name = "YYYY-MM-DD", conflicts_with_all = ["random", "day", "seed"])]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// Profile to play as; each player has separate history and statistics
+    #[arg(short = 'p', long, value_name = "NAME")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub player: Option<String>,
+    /// Resume the unfinished game in the state file without asking
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub resume: bool,
+    /// Offer solver hints between guesses
+    #[arg(short = 'v', long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub solver: bool,
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub solver_options: SolverArgs,
+    /// Print a spoiler-free emoji grid of each finished game
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub share: bool,
+    /// Also write the grid of the last finished game to a file
+    #[arg(long, value_name = "PATH")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share_file: Option<String>,
+    #[command(flatten)]
+    #[serde(flatten)]
+    pub grid: GridArgs,
+    /// JSON file with default options; command-line options take precedence
+    #[arg(short, long, value_name = "PATH")]
+    #[serde(skip)]
+    pub config: Option<String>,
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+/// Word lists to play or solve with; the builtin ones unless both files are given.
+#[derive(Args, Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct WordSetArgs {
+    /// File with the possible answers, one word per line
+    #[arg(short, long, value_name = "PATH")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub final_set: Option<String>,
+    /// File with the allowed guesses, one word per line
+    #[arg(short, long, value_name = "PATH")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub acceptable_set: Option<String>,
-    pub state: Option<String>,
-    pub word: Option<String>,
+    /// Word length; other than 5 needs custom word sets
+    #[arg(short, long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+}
+
+impl WordSetArgs {
+    pub fn word_length(&self) -> usize {
+        self.length.unwrap_or(DEFAULT_WORD_LENGTH)
+    }
+
+    /// Loads and validates the final and acceptable word lists.
+    pub fn load(&self) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
+        let word_length = self.word_length();
+        if word_length == 0 || word_length > MAX_WORD_LENGTH {
+            return Err(format!("--length must be between 1 and {MAX_WORD_LENGTH}").into());
+        }
+
+        match (&self.final_set, &self.acceptable_set) {
+            (Some(f_path), Some(a_path)) => {
+                let (final_words, acceptable_words) =
+                    load_and_validate_word_sets(f_path, a_path, word_length)?;
+                if final_words.is_empty() {
+                    return Err(format!("final word list {f_path} has no words").into());
+                }
+                Ok((final_words, acceptable_words))
+            }
+            (Some(_), None) => Err("--final-set requires --acceptable-set".into()),
+            _ if word_length != DEFAULT_WORD_LENGTH => Err(format!(
+                "builtin word lists only contain {DEFAULT_WORD_LENGTH}-letter words, use --final-set and --acceptable-set"
+            )
+            .into()),
+            _ => Ok((
+                builtin_words::FINAL.iter().map(|&s| s.to_string()).collect(),
+                builtin_words::ACCEPTABLE
+                    .iter()
+                    .map(|&s| s.to_string())
+                    .collect(),
+            )),
+        }
+    }
+}
+
+/// How the solver computes its recommendations.
+#[derive(Args, Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct SolverArgs {
+    /// Worker threads for solver recommendations; one per CPU by default
+    #[arg(long, value_name = "N")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+    /// How the solver ranks recommended guesses; `rec <NAME>` overrides it once
+    #[arg(long, value_name = "NAME")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<StrategyKind>,
+}
+
+impl SolverArgs {
+    pub fn strategy(&self) -> StrategyKind {
+        self.strategy.unwrap_or_default()
+    }
+}
+
+/// How shared grids are drawn.
+#[derive(Args, Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct GridArgs {
+    /// Use G/Y/R letters instead of emoji in the grid
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub ascii: bool,
+}
+
+impl Config {
+    /// Fills unset options from the JSON file given by `--config`, if any.
+    pub fn resolve(self) -> Result<Config, Box<dyn Error>> {
+        match self.config.clone() {
+            Some(path) => self.merged_with_file(&path),
+            None => Ok(self),
+        }
+    }
+
+    /// Overlays the options set in `self` onto the JSON config file at `path`.
+    pub fn merged_with_file(self, path: &str) -> Result<Config, Box<dyn Error>> {
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("cannot read config file {path}: {e}"))?;
+        let mut merged: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&content)
+            .map_err(|e| format!("error parsing config file {path}: {e}"))?;
+        // report bad values against the file before they get mixed with the cli
+        serde_json::from_value::<Config>(merged.clone().into())
+            .map_err(|e| format!("error parsing config file {path}: {e}"))?;
+
+        if let serde_json::Value::Object(cli) = serde_json::to_value(&self)? {
+            merged.extend(cli);
+        }
+        let mut config: Config = serde_json::from_value(merged.into())?;
+        config.config = self.config;
+        Ok(config)
+    }
+}
+
+/// Accepts `"difficult": true` from older configs as well as a level name.
+fn deserialize_difficulty<'de, D>(deserializer: D) -> Result<Option<Difficulty>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Setting {
+        Flag(bool),
+        Level(Difficulty),
+    }
+
+    Ok(match Option::<Setting>::deserialize(deserializer)? {
+        Some(Setting::Flag(true)) => Some(Difficulty::Hard),
+        Some(Setting::Flag(false)) => Some(Difficulty::Normal),
+        Some(Setting::Level(level)) => Some(level),
+        None => None,
+    })
 }
+
 pub fn is_valid(
     guess: &str,
-    _diff_mode: bool,
+    word_length: usize,
+    difficulty: Difficulty,
     guess_history: &[String],
-    state_history: &[[char; 5]],
+    state_history: &[Feedback],
     acceptable_words: &[String],
 ) -> bool {
+    validate_guess(
+        guess,
+        word_length,
+        difficulty,
+        guess_history,
+        state_history,
+        acceptable_words,
+    )
+    .is_ok()
+}
+
+/// Like [`is_valid`], but reports the first rule the guess breaks.
+pub fn validate_guess(
+    guess: &str,
+    word_length: usize,
+    difficulty: Difficulty,
+    guess_history: &[String],
+    state_history: &[Feedback],
+    acceptable_words: &[String],
+) -> Result<(), GuessError> {
     let trimmed_guess = guess.trim();
     let lower_guess = trimmed_guess.to_lowercase();
 
-    if trimmed_guess.len() != 5 || !acceptable_words.contains(&lower_guess) {
-        return false;
+    let guess_length = trimmed_guess.chars().count();
+    if guess_length != word_length {
+        return Err(GuessError::WrongLength {
+            expected: word_length,
+            actual: guess_length,
+        });
+    }
+    if !acceptable_words.contains(&lower_guess) {
+        return Err(GuessError::NotInWordList { word: lower_guess });
     }
 
-    if !_diff_mode {
-        return true;
+    if difficulty == Difficulty::Normal {
+        return Ok(());
     }
 
     let new_guess_chars: Vec<char> = lower_guess.chars().collect();
@@ -65,17 +417,20 @@ pub fn is_valid(
 
     for i in 0..guess_history.len() {
         let prev_guess_chars: Vec<char> = guess_history[i].to_lowercase().chars().collect();
-        let prev_state = state_history[i];
+        let prev_state = state_history[i].states();
 
-        for j in 0..5 {
-            if prev_state[j] == 'G' && prev_guess_chars[j] != new_guess_chars[j] {
-                return false;
+        for j in 0..prev_state.len() {
+            if prev_state[j] == LetterState::Correct && prev_guess_chars[j] != new_guess_chars[j] {
+                return Err(GuessError::MustUseGreen {
+                    letter: prev_guess_chars[j],
+                    position: j + 1,
+                });
             }
         }
 
         let mut required_yellows = [0; 26];
-        for j in 0..5 {
-            if prev_state[j] == 'Y' {
+        for j in 0..prev_state.len() {
+            if prev_state[j] == LetterState::Present {
                 let char_at_yellow_pos = prev_guess_chars[j];
                 if let Some(index) = (char_at_yellow_pos as u8).checked_sub(b'a') {
                     required_yellows[index as usize] += 1;
@@ -85,99 +440,199 @@ pub fn is_valid(
 
         for j in 0..26 {
             if new_guess_char_counts[j] < required_yellows[j] {
-                return false;
+                return Err(GuessError::MustContain {
+                    letter: (b'a' + j as u8) as char,
+                    count: required_yellows[j],
+                });
             }
         }
+
+        if difficulty == Difficulty::Strict {
+            check_strict_constraints(&new_guess_chars, &prev_guess_chars, prev_state)?;
+        }
     }
-    true
+    Ok(())
 }
 
-pub fn color_state(guess: &str, answer: &str) -> [char; 5] {
-    let guess_chars: Vec<char> = guess.chars().collect();
-    let answer_chars: Vec<char> = answer.chars().collect();
-    let mut state = ['R'; 5];
-
-    let mut answer_counts = [0; 26];
-    for &c in &answer_chars {
-        if let Some(index) = (c.to_ascii_uppercase() as u8).checked_sub(b'A') {
-            answer_counts[index as usize] += 1;
+/// Extra checks for strict mode against one earlier guess: yellow and absent
+/// letters may not be repeated in the same position, every revealed letter
+/// must appear at least as often as it was marked, and a letter that was
+/// also marked absent must appear exactly that often.
+fn check_strict_constraints(
+    new_guess_chars: &[char],
+    prev_guess_chars: &[char],
+    prev_state: &[LetterState],
+) -> Result<(), GuessError> {
+    let mut known_counts = [0; 26];
+    let mut has_absent = [false; 26];
+    for (j, &state) in prev_state.iter().enumerate() {
+        if state != LetterState::Correct && new_guess_chars[j] == prev_guess_chars[j] {
+            return Err(GuessError::MustMoveLetter {
+                letter: prev_guess_chars[j],
+                position: j + 1,
+            });
         }
-    }
-    for i in 0..5 {
-        if guess_chars[i] == answer_chars[i] {
-            state[i] = 'G';
-            if let Some(index) = (guess_chars[i].to_ascii_uppercase() as u8).checked_sub(b'A') {
-                answer_counts[index as usize] -= 1;
+        if let Some(index) = (prev_guess_chars[j] as u8).checked_sub(b'a') {
+            match state {
+                LetterState::Absent => has_absent[index as usize] = true,
+                _ => known_counts[index as usize] += 1,
             }
         }
     }
-    for i in 0..5 {
-        if state[i] != 'G'
-            && let Some(index) = (guess_chars[i].to_ascii_uppercase() as u8).checked_sub(b'A')
-            && answer_counts[index as usize] > 0
-        {
-            state[i] = 'Y';
-            answer_counts[index as usize] -= 1;
+
+    let mut new_counts = [0; 26];
+    for &c in new_guess_chars {
+        if let Some(index) = (c as u8).checked_sub(b'a') {
+            new_counts[index as usize] += 1;
         }
     }
-    state
+
+    for i in 0..26 {
+        let letter = (b'a' + i as u8) as char;
+        if new_counts[i] < known_counts[i] {
+            return Err(GuessError::MustContain {
+                letter,
+                count: known_counts[i],
+            });
+        }
+        if has_absent[i] && new_counts[i] > known_counts[i] {
+            return Err(GuessError::TooManyLetter {
+                letter,
+                count: known_counts[i],
+            });
+        }
+    }
+    Ok(())
 }
-pub fn keyboard_state_update(keyboard_state: &mut [char; 26], guess: &str, state: [char; 5]) {
-    let guess_chars: Vec<char> = guess.chars().collect();
 
-    for i in 0..5 {
-        let current_char = guess_chars[i].to_ascii_uppercase();
-        let index = (current_char as u8 - b'A') as usize;
+pub fn color_state(guess: &str, answer: &str) -> Feedback {
+    Feedback::compute(guess, answer)
+}
+/// Keyboard hint for each letter `A..=Z`; `None` until the letter is guessed.
+pub type Keyboard = [Option<LetterState>; 26];
 
-        let current_keyboard_state = keyboard_state[index];
+pub fn keyboard_state_update(keyboard_state: &mut Keyboard, guess: &str, state: Feedback) {
+    for (c, &new_state) in guess.chars().zip(state.states()) {
+        let index = (c.to_ascii_uppercase() as u8 - b'A') as usize;
+        // G beats Y beats R, so a letter never gets downgraded
+        keyboard_state[index] = keyboard_state[index].max(Some(new_state));
+    }
+}
 
-        let new_state = state[i];
+/// Formats the keyboard in the `X`/`R`/`Y`/`G` text protocol.
+pub fn keyboard_to_string(keyboard_state: &Keyboard) -> String {
+    keyboard_state
+        .iter()
+        .map(|state| state.map_or('X', LetterState::as_char))
+        .collect()
+}
 
-        match (current_keyboard_state, new_state) {
-            ('G', _) => {}
-            (_, 'G') => keyboard_state[index] = 'G',
-            ('Y', _) => {}
-            (_, 'Y') => keyboard_state[index] = 'Y',
-            ('R', _) => {}
-            (_, 'R') => keyboard_state[index] = 'R',
-            _ => {}
-        }
+fn style_letter(c: char, state: Option<LetterState>) -> console::StyledObject<char> {
+    match state {
+        Some(LetterState::Correct) => style(c).green(),
+        Some(LetterState::Present) => style(c).yellow(),
+        Some(LetterState::Absent) => style(c).red(),
+        None => style(c).white(),
     }
 }
 
-pub fn print_result(state: [char; 5], guess: &str) {
-    for (i, c) in guess.chars().enumerate() {
-        let styled_char = match state[i] {
-            'G' => style(c).green(),
-            'Y' => style(c).yellow(),
-            'R' => style(c).red(),
-            _ => style(c).white(),
-        };
-        print!("{styled_char}");
+pub fn print_result(state: Feedback, guess: &str) {
+    for (c, &letter_state) in guess.chars().zip(state.states()) {
+        print!("{}", style_letter(c, Some(letter_state)));
     }
     print!(" ");
 }
 
-pub fn print_keyboard_state(keyboard_state: &[char; 26]) {
-    for (i, &c) in keyboard_state.iter().enumerate() {
-        let styled_char = match c {
-            'G' => style((b'A' + i as u8) as char).green(),
-            'Y' => style((b'A' + i as u8) as char).yellow(),
-            'R' => style((b'A' + i as u8) as char).red(),
-            _ => style((b'A' + i as u8) as char).white(),
-        };
-        print!("{styled_char}");
+pub fn print_keyboard_state(keyboard_state: &Keyboard) {
+    for (i, &state) in keyboard_state.iter().enumerate() {
+        print!("{}", style_letter((b'A' + i as u8) as char, state));
     }
     println!();
 }
-pub fn print_stats(
-    is_tty: bool,
-    successful_games: u32,
-    failed_games: u32,
-    total_successful_attempts: u32,
-    guess_frequency: &HashMap<String, u32>,
-) {
-    let played_games = successful_games + failed_games;
+/// Summary of a game history, as shown by [`print_stats`] and the `stats` command.
+#[derive(Serialize, Debug, Default)]
+pub struct GameStats {
+    pub played: u32,
+    pub successful_games: u32,
+    pub failed_games: u32,
+    pub total_successful_attempts: u32,
+    /// Share of games won, between 0 and 1.
+    pub win_rate: f64,
+    /// Average number of guesses over won games.
+    pub average_guesses: f64,
+    /// Wins in a row up to the most recent game.
+    pub current_streak: u32,
+    pub max_streak: u32,
+    /// `distribution[i]` is the number of games won in `i + 1` guesses.
+    pub distribution: Vec<u32>,
+    /// Most used guesses, most frequent first.
+    pub top_words: Vec<(String, u32)>,
+    #[serde(skip)]
+    pub guess_frequency: HashMap<String, u32>,
+}
+
+/// Computes statistics over `games`, oldest first. A game counts as won
+/// when its last guess matches the answer.
+pub fn summarize_games(games: &[GameRecord]) -> GameStats {
+    let mut stats = GameStats {
+        distribution: vec![0; game::DEFAULT_MAX_ATTEMPTS],
+        ..GameStats::default()
+    };
+    for record in games {
+        stats.played += 1;
+        if stats.distribution.len() < record.max_guesses {
+            stats.distribution.resize(record.max_guesses, 0);
+        }
+        if record
+            .guesses
+            .last()
+            .map(|g| color_state(g, &record.answer).is_win())
+            .unwrap_or(false)
+        {
+            let attempts = record.guesses.len();
+            stats.successful_games += 1;
+            stats.total_successful_attempts += attempts as u32;
+            if stats.distribution.len() < attempts {
+                stats.distribution.resize(attempts, 0);
+            }
+            stats.distribution[attempts - 1] += 1;
+            stats.current_streak += 1;
+            stats.max_streak = stats.max_streak.max(stats.current_streak);
+        } else {
+            stats.failed_games += 1;
+            stats.current_streak = 0;
+        }
+        for guess in &record.guesses {
+            *stats
+                .guess_frequency
+                .entry(guess.to_lowercase())
+                .or_insert(0) += 1;
+        }
+    }
+
+    if stats.played > 0 {
+        stats.win_rate = stats.successful_games as f64 / stats.played as f64;
+    }
+    if stats.successful_games > 0 {
+        stats.average_guesses =
+            stats.total_successful_attempts as f64 / stats.successful_games as f64;
+    }
+
+    let mut frequent_guesses: Vec<(String, u32)> = stats
+        .guess_frequency
+        .iter()
+        .map(|(word, &count)| (word.clone(), count))
+        .collect();
+    frequent_guesses.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    frequent_guesses.truncate(5);
+    stats.top_words = frequent_guesses;
+    stats
+}
+
+pub fn print_stats(is_tty: bool, stats: &GameStats) {
+    let successful_games = stats.successful_games;
+    let failed_games = stats.failed_games;
+    let played_games = stats.played;
 
     if played_games == 0 {
         if is_tty {
@@ -186,12 +641,8 @@ pub fn print_stats(
         return;
     }
 
-    let success_rate: f64 = successful_games as f64 / played_games as f64;
-    let avg_attempts: f64 = if successful_games > 0 {
-        total_successful_attempts as f64 / successful_games as f64
-    } else {
-        0.0
-    };
+    let success_rate = stats.win_rate;
+    let avg_attempts = stats.average_guesses;
 
     if is_tty {
         println!("\n--- game statistic ---");
@@ -205,10 +656,7 @@ pub fn print_stats(
         println!("{successful_games} {failed_games} {avg_attempts:.2}");
     }
 
-    let mut frequent_guesses: Vec<_> = guess_frequency.iter().collect();
-    frequent_guesses.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
-
-    let top_5_guesses = frequent_guesses.iter().take(5);
+    let top_5_guesses = stats.top_words.iter();
 
     if is_tty {
         for (word, count) in top_5_guesses {
@@ -220,10 +668,151 @@ pub fn print_stats(
             .collect();
         println!("{}", stats_line.join(" "));
     }
+
+    if is_tty {
+        println!("--- streak ---");
+        println!(
+            "current: {} | max: {}",
+            stats.current_streak, stats.max_streak
+        );
+        println!("--- guess distribution ---");
+        print_distribution_chart(&stats.distribution);
+    } else {
+        // keyword-prefixed so the lines stay parseable as fields are added
+        println!("STREAK {} {}", stats.current_streak, stats.max_streak);
+        let counts: Vec<String> = stats.distribution.iter().map(|c| c.to_string()).collect();
+        println!("DISTRIBUTION {}", counts.join(" "));
+    }
 }
-pub fn get_answer_for_day(day: u32, seed: u64) -> String {
+
+/// Width of the longest bar in the guess distribution chart.
+const CHART_WIDTH: u32 = 30;
+
+pub fn print_distribution_chart(distribution: &[u32]) {
+    let max_count = distribution.iter().copied().max().unwrap_or(0).max(1);
+    let label_width = distribution.len().to_string().len();
+    for (i, &count) in distribution.iter().enumerate() {
+        // every row gets at least one block so zero counts still line up
+        let bar_len = (count * CHART_WIDTH / max_count).max(1) as usize;
+        let bar = "█".repeat(bar_len);
+        let bar = if count > 0 {
+            style(bar).green()
+        } else {
+            style(bar).dim()
+        };
+        println!("{:>label_width$} {bar} {count}", i + 1);
+    }
+}
+
+/// One row of the leaderboard.
+#[derive(Serialize, Debug)]
+pub struct LeaderboardEntry {
+    pub player: String,
+    #[serde(flatten)]
+    pub stats: GameStats,
+}
+
+/// Statistics of every profile that played a game, best first: higher win
+/// rate, then fewer average guesses, then more games played.
+pub fn leaderboard(games: &[GameRecord]) -> Vec<LeaderboardEntry> {
+    let mut by_player: HashMap<&str, Vec<GameRecord>> = HashMap::new();
+    for record in games {
+        by_player
+            .entry(record.player_name())
+            .or_default()
+            .push(record.clone());
+    }
+
+    let mut entries: Vec<LeaderboardEntry> = by_player
+        .into_iter()
+        .map(|(player, games)| LeaderboardEntry {
+            player: player.to_string(),
+            stats: summarize_games(&games),
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.stats
+            .win_rate
+            .total_cmp(&a.stats.win_rate)
+            .then_with(|| a.stats.average_guesses.total_cmp(&b.stats.average_guesses))
+            .then_with(|| b.stats.played.cmp(&a.stats.played))
+            .then_with(|| a.player.cmp(&b.player))
+    });
+    entries
+}
+
+pub fn print_leaderboard(is_tty: bool, entries: &[LeaderboardEntry]) {
+    if !is_tty {
+        for entry in entries {
+            println!(
+                "{} {} {:.2} {:.2} {}",
+                entry.player,
+                entry.stats.played,
+                entry.stats.win_rate,
+                entry.stats.average_guesses,
+                entry.stats.max_streak
+            );
+        }
+        return;
+    }
+
+    let name_width = entries
+        .iter()
+        .map(|entry| entry.player.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("player".len());
+    println!("\n--- leaderboard ---");
+    println!(
+        "{:>4}  {:<name_width$}  {:>6}  {:>7}  {:>7}  {:>6}",
+        "#", "player", "played", "win", "avg", "streak"
+    );
+    for (rank, entry) in entries.iter().enumerate() {
+        println!(
+            "{:>4}  {:<name_width$}  {:>6}  {:>6.2}%  {:>7.2}  {:>6}",
+            rank + 1,
+            style(&entry.player).bold(),
+            entry.stats.played,
+            entry.stats.win_rate * 100.0,
+            entry.stats.average_guesses,
+            entry.stats.max_streak
+        );
+    }
+}
+
+/// Stable 64-bit FNV-1a hash of a word list, independent of the Rust version.
+pub fn word_list_hash(words: &[String]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for word in words {
+        for byte in word.bytes().chain(std::iter::once(b'\n')) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+/// Identifies a final word list in saved games: `builtin`, or `custom-` and
+/// the list's hash for lists loaded from a file.
+pub fn word_list_id(final_words: &[String]) -> String {
+    let is_builtin = final_words.len() == builtin_words::FINAL.len()
+        && final_words
+            .iter()
+            .zip(builtin_words::FINAL)
+            .all(|(a, b)| a == b);
+    if is_builtin {
+        "builtin".to_string()
+    } else {
+        format!("custom-{:016x}", word_list_hash(final_words))
+    }
+}
+
+/// Picks the answer for `day` (1-based) from a `seed`-shuffled copy of `final_words`.
+///
+/// Panics unless `day` is between 1 and `final_words.len()`.
+pub fn get_answer_for_day(day: u32, seed: u64, final_words: &[String]) -> String {
     let mut rng = StdRng::seed_from_u64(seed);
-    let mut words: Vec<&str> = builtin_words::FINAL.to_vec();
+    let mut words: Vec<&String> = final_words.iter().collect();
     words.shuffle(&mut rng);
     words[(day - 1) as usize].to_string()
 }
@@ -231,6 +820,7 @@ pub fn get_answer_for_day(day: u32, seed: u64) -> String {
 pub fn load_and_validate_word_sets(
     final_path: &str,
     acceptable_path: &str,
+    word_length: usize,
 ) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
     let final_content = fs::read_to_string(final_path)?;
     let acceptable_content = fs::read_to_string(acceptable_path)?;
@@ -239,57 +829,380 @@ pub fn load_and_validate_word_sets(
         .lines()
         .map(|s| s.trim().to_lowercase())
         .collect();
-    let final_words_set: HashSet<String> = final_words_vec.iter().cloned().collect();
-    if final_words_vec.len() != final_words_set.len() {
-        return Err("more than one".into());
-    }
-    for word in &final_words_vec {
-        if word.len() != 5 || !word.chars().all(|c| c.is_alphabetic()) {
-            return Err("incorrect".into());
-        }
-    }
+    validate_words(final_path, &final_words_vec, word_length)?;
     final_words_vec.sort();
 
     let mut acceptable_words_vec: Vec<String> = acceptable_content
         .lines()
         .map(|s| s.trim().to_lowercase())
         .collect();
-    let acceptable_words_set: HashSet<String> = acceptable_words_vec.iter().cloned().collect();
-    if acceptable_words_vec.len() != acceptable_words_set.len() {
-        return Err("more than one".into());
-    }
-    for word in &acceptable_words_vec {
-        if word.len() != 5 || !word.chars().all(|c| c.is_alphabetic()) {
-            return Err("incorrect".into());
-        }
-    }
+    validate_words(acceptable_path, &acceptable_words_vec, word_length)?;
     acceptable_words_vec.sort();
 
-    // check subset
-    if !final_words_set.is_subset(&acceptable_words_set) {
-        return Err("not subset".into());
+    if let Some(word) = final_words_vec
+        .iter()
+        .find(|word| acceptable_words_vec.binary_search(word).is_err())
+    {
+        return Err(format!(
+            "'{word}' from {final_path} is missing from the acceptable word list {acceptable_path}"
+        )
+        .into());
     }
 
     Ok((final_words_vec, acceptable_words_vec))
 }
-pub fn load_state(path: &str) -> Result<Option<GameState>, Box<dyn Error>> {
-    let file_content = fs::read_to_string(path);
 
-    if file_content.is_err() {
-        return Ok(None);
+/// Checks that every word in the list at `path` is `word_length` letters
+/// from a to z and appears only once.
+fn validate_words(path: &str, words: &[String], word_length: usize) -> Result<(), Box<dyn Error>> {
+    let mut seen = HashSet::with_capacity(words.len());
+    for word in words {
+        if word.chars().count() != word_length {
+            return Err(format!("'{word}' in {path} is not {word_length} letters long").into());
+        }
+        // the keyboard and feedback only know the letters a to z
+        if !word.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(format!("'{word}' in {path} has letters other than a to z").into());
+        }
+        if !seen.insert(word.as_str()) {
+            return Err(format!("'{word}' appears more than once in {path}").into());
+        }
     }
+    Ok(())
+}
 
-    match serde_json::from_str::<GameState>(&file_content?) {
-        Ok(loaded_state) => Ok(Some(loaded_state)),
-        Err(e) => {
-            eprintln!("Error parsing state file: {e}");
-            Err("Invalid state file format".into())
+/// How long to wait for another process to release a state file.
+pub(crate) const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Advisory lock on a state file, released when dropped.
+///
+/// The lock is taken on a `<path>.lock` file next to the state, because
+/// saving replaces the state file itself.
+pub struct StateLock {
+    _file: fs::File,
+}
+
+pub fn lock_state(path: &str) -> Result<StateLock, Box<dyn Error>> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(format!("{path}.lock"))?;
+    let deadline = Instant::now() + LOCK_TIMEOUT;
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(StateLock { _file: file }),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(format!("state file {path} is locked by another process").into());
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
         }
     }
 }
 
+/// Loads the state file at `path`, or `None` if it does not exist.
+///
+/// Files with an older schema are migrated; the original is first copied to
+/// a `.bak` file next to it and then rewritten in the current format.
+///
+/// When the lock file cannot be created, e.g. in a read-only directory, the
+/// file is read without the lock.
+pub fn load_state(path: &str) -> Result<Option<GameState>, Box<dyn Error>> {
+    if !std::path::Path::new(path).exists() {
+        return Ok(None);
+    }
+    let _lock = match lock_state(path) {
+        Ok(lock) => Some(lock),
+        Err(e) if is_read_only_error(e.as_ref()) => None,
+        Err(e) => return Err(e),
+    };
+    load_state_locked(path)
+}
+
+fn is_read_only_error(e: &(dyn Error + 'static)) -> bool {
+    e.downcast_ref::<std::io::Error>().is_some_and(|e| {
+        matches!(
+            e.kind(),
+            std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem
+        )
+    })
+}
+
+/// Adds a finished game to the state file, clearing its player's
+/// unfinished one, and returns the whole history.
+///
+/// The file is re-read under the lock, so games saved by another process
+/// sharing the file since it was loaded are kept.
+pub fn append_game(path: &str, record: GameRecord) -> Result<GameState, Box<dyn Error>> {
+    let _lock = lock_state(path)?;
+    let mut state = load_state_locked(path)?.unwrap_or_else(|| GameState::new(Vec::new()));
+    state
+        .in_progress
+        .retain(|saved| saved.player != record.player);
+    state.games.push(record);
+    state.total_rounds = state.games.len() as u32;
+    save_state(path, &state)?;
+    Ok(state)
+}
+
+/// Saves (or with `None` clears) the unfinished game of `player` in the
+/// state file.
+pub fn save_in_progress(
+    path: &str,
+    player: Option<&str>,
+    record: Option<GameRecord>,
+) -> Result<(), Box<dyn Error>> {
+    let _lock = lock_state(path)?;
+    let mut state = load_state_locked(path)?.unwrap_or_else(|| GameState::new(Vec::new()));
+    state
+        .in_progress
+        .retain(|saved| saved.player.as_deref() != player);
+    state.in_progress.extend(record);
+    save_state(path, &state)
+}
+
+fn load_state_locked(path: &str) -> Result<Option<GameState>, Box<dyn Error>> {
+    let file_content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        // anything else would have the file replaced by an empty history
+        Err(e) => return Err(format!("cannot read state file {path}: {e}").into()),
+    };
+
+    let parse_error = |e: &dyn std::fmt::Display| {
+        eprintln!("Error parsing state file: {e}");
+        "Invalid state file format"
+    };
+    let mut value: serde_json::Value =
+        serde_json::from_str(&file_content).map_err(|e| parse_error(&e))?;
+    let old_version = migrate::migrate(&mut value)?;
+    let loaded_state: GameState = serde_json::from_value(value).map_err(|e| parse_error(&e))?;
+
+    if old_version < migrate::CURRENT_SCHEMA_VERSION {
+        let backup_path = format!(
+            "{path}.v{old_version}-{}.bak",
+            Utc::now().format("%Y%m%d%H%M%S")
+        );
+        fs::write(&backup_path, &file_content)?;
+        save_state(path, &loaded_state)?;
+        eprintln!(
+            "Upgraded state file {path} from schema version {old_version} to {}, original saved as {backup_path}",
+            migrate::CURRENT_SCHEMA_VERSION
+        );
+    }
+    Ok(Some(loaded_state))
+}
+
+/// Writes `state` to a temporary file and renames it over `path`, so a
+/// crash never leaves a truncated state file behind.
 pub fn save_state(path: &str, state: &GameState) -> Result<(), Box<dyn Error>> {
     let json_string = serde_json::to_string_pretty(state)?;
-    fs::write(path, json_string)?;
+    let tmp_path = format!("{path}.tmp{}", std::process::id());
+    let mut file = fs::File::create(&tmp_path)?;
+    file.write_all(json_string.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Checks `guess` in strict mode after `history` was played against `answer`.
+    fn strict(answer: &str, history: &[&str], guess: &str) -> Result<(), GuessError> {
+        let guesses: Vec<String> = history.iter().map(|word| word.to_string()).collect();
+        let states: Vec<Feedback> = history
+            .iter()
+            .map(|word| color_state(word, answer))
+            .collect();
+        let mut acceptable = guesses.clone();
+        acceptable.push(guess.to_string());
+        validate_guess(
+            guess,
+            answer.len(),
+            Difficulty::Strict,
+            &guesses,
+            &states,
+            &acceptable,
+        )
+    }
+
+    #[test]
+    fn strict_accepts_a_consistent_guess() {
+        // CRANE against TAPIR: R and A are yellow, C, N and E absent
+        assert_eq!(strict("tapir", &["crane"], "ratio"), Ok(()));
+    }
+
+    #[test]
+    fn strict_rejects_a_yellow_in_the_same_place() {
+        assert_eq!(
+            strict("tapir", &["crane"], "braid"),
+            Err(GuessError::MustMoveLetter {
+                letter: 'r',
+                position: 2
+            })
+        );
+    }
+
+    #[test]
+    fn strict_rejects_an_absent_letter_in_the_same_place() {
+        assert_eq!(
+            strict("tapir", &["crane"], "carts"),
+            Err(GuessError::MustMoveLetter {
+                letter: 'c',
+                position: 1
+            })
+        );
+    }
+
+    #[test]
+    fn strict_rejects_an_absent_letter_elsewhere() {
+        assert_eq!(
+            strict("tapir", &["crane"], "ranis"),
+            Err(GuessError::TooManyLetter {
+                letter: 'n',
+                count: 0
+            })
+        );
+    }
+
+    #[test]
+    fn strict_rejects_more_copies_than_a_yellow_and_absent_letter_allows() {
+        // EERIE against BAKER: the first E is yellow, the other two absent,
+        // so the answer has exactly one E
+        assert_eq!(strict("baker", &["eerie"], "bread"), Ok(()));
+        assert_eq!(
+            strict("baker", &["eerie"], "breed"),
+            Err(GuessError::TooManyLetter {
+                letter: 'e',
+                count: 1
+            })
+        );
+    }
+
+    #[test]
+    fn strict_counts_greens_and_yellows_together() {
+        // EERIE against GEESE: two green Es and a yellow one, so three Es
+        assert_eq!(
+            strict("geese", &["eerie"], "seame"),
+            Err(GuessError::MustContain {
+                letter: 'e',
+                count: 3
+            })
+        );
+    }
+
+    #[test]
+    fn strict_still_applies_hard_mode_rules() {
+        assert_eq!(
+            strict("tapir", &["crane"], "bolts"),
+            Err(GuessError::MustContain {
+                letter: 'a',
+                count: 1
+            })
+        );
+        assert_eq!(
+            strict("tapir", &["tares"], "ratio"),
+            Err(GuessError::MustUseGreen {
+                letter: 't',
+                position: 1
+            })
+        );
+    }
+
+    /// A state file path in a fresh temporary directory.
+    fn temp_state(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("wordle-state-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("state.json").to_str().unwrap().to_string()
+    }
+
+    fn record(answer: &str, player: Option<&str>) -> GameRecord {
+        GameRecord {
+            answer: answer.to_string(),
+            guesses: vec![answer.to_string()],
+            max_guesses: 6,
+            player: player.map(str::to_string),
+            ..GameRecord::default()
+        }
+    }
+
+    fn answers(records: &[GameRecord]) -> Vec<&str> {
+        records
+            .iter()
+            .map(|record| record.answer.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn appending_keeps_games_saved_by_another_session() {
+        let path = temp_state("append");
+        // two sessions that loaded the file before either finished a game
+        append_game(&path, record("TAPIR", None)).unwrap();
+        let state = append_game(&path, record("CIGAR", Some("alice"))).unwrap();
+        assert_eq!(answers(&state.games), ["TAPIR", "CIGAR"]);
+        assert_eq!(state.total_rounds, 2);
+
+        let loaded = load_state(&path).unwrap().unwrap();
+        assert_eq!(answers(&loaded.games), ["TAPIR", "CIGAR"]);
+    }
+
+    #[test]
+    fn unreadable_state_is_an_error_and_left_alone() {
+        let path = temp_state("invalid");
+        append_game(&path, record("TAPIR", None)).unwrap();
+        let mut content = fs::read(&path).unwrap();
+        content.push(0xff);
+        fs::write(&path, &content).unwrap();
+
+        assert!(load_state(&path).is_err());
+        assert!(append_game(&path, record("CIGAR", None)).is_err());
+        assert!(save_in_progress(&path, None, Some(record("CIGAR", None))).is_err());
+        assert_eq!(fs::read(&path).unwrap(), content);
+
+        // not JSON at all
+        fs::write(&path, "not json").unwrap();
+        assert!(append_game(&path, record("CIGAR", None)).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "not json");
+    }
+
+    #[test]
+    fn missing_state_is_empty() {
+        let path = temp_state("missing");
+        assert!(load_state(&path).unwrap().is_none());
+        let state = append_game(&path, record("TAPIR", None)).unwrap();
+        assert_eq!(answers(&state.games), ["TAPIR"]);
+    }
+
+    #[test]
+    fn clearing_a_game_in_progress_keeps_other_players_games() {
+        let path = temp_state("in-progress");
+        save_in_progress(&path, None, Some(record("TAPIR", None))).unwrap();
+        save_in_progress(&path, Some("alice"), Some(record("CIGAR", Some("alice")))).unwrap();
+        save_in_progress(&path, Some("bob"), Some(record("HELLO", Some("bob")))).unwrap();
+
+        save_in_progress(&path, None, None).unwrap();
+        let state = load_state(&path).unwrap().unwrap();
+        assert_eq!(answers(&state.in_progress), ["CIGAR", "HELLO"]);
+
+        save_in_progress(&path, Some("alice"), None).unwrap();
+        let state = load_state(&path).unwrap().unwrap();
+        assert_eq!(answers(&state.in_progress), ["HELLO"]);
+        assert_eq!(state.in_progress_of(Some("bob")).unwrap().answer, "HELLO");
+        assert!(state.in_progress_of(None).is_none());
+
+        // finishing bob's game clears it too
+        let state = append_game(&path, record("HELLO", Some("bob"))).unwrap();
+        assert!(state.in_progress.is_empty());
+    }
+}
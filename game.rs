@@ -0,0 +1,303 @@
+use crate::feedback::Feedback;
+use crate::function::{self, GameRecord, Keyboard};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Default number of guesses a player gets before the game is lost.
+pub const DEFAULT_MAX_ATTEMPTS: usize = 6;
+
+/// How strictly later guesses must follow earlier feedback.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    #[default]
+    Normal,
+    /// Greens stay in place and yellows must be reused.
+    Hard,
+    /// Every revealed constraint applies: yellows move, absent letters and
+    /// known letter counts are respected.
+    Strict,
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            "strict" => Ok(Difficulty::Strict),
+            other => Err(format!(
+                "unknown difficulty '{other}', expected normal, hard or strict"
+            )),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GameStatus {
+    InProgress,
+    Won,
+    Lost,
+}
+
+/// What happened after a guess was accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuessResult {
+    pub state: Feedback,
+    pub attempts: usize,
+    pub status: GameStatus,
+}
+
+/// Why a guess was rejected. Positions are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuessError {
+    WrongLength {
+        expected: usize,
+        actual: usize,
+    },
+    NotInWordList {
+        word: String,
+    },
+    /// Hard mode: a green letter must stay where it was found.
+    MustUseGreen {
+        letter: char,
+        position: usize,
+    },
+    /// Hard mode: a revealed letter must appear at least `count` times.
+    MustContain {
+        letter: char,
+        count: usize,
+    },
+    /// Strict mode: the letter is known not to be at this position.
+    MustMoveLetter {
+        letter: char,
+        position: usize,
+    },
+    /// Strict mode: the letter appears at most `count` times in the answer.
+    TooManyLetter {
+        letter: char,
+        count: usize,
+    },
+    /// The game has already been won or lost.
+    GameOver,
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuessError::WrongLength { expected, actual } => {
+                write!(f, "guess must be {expected} letters long, got {actual}")
+            }
+            GuessError::NotInWordList { word } => {
+                write!(f, "{} is not in the word list", word.to_uppercase())
+            }
+            GuessError::MustUseGreen { letter, position } => write!(
+                f,
+                "letter {} must stay in position {position}",
+                letter.to_ascii_uppercase()
+            ),
+            GuessError::MustContain { letter, count } => write!(
+                f,
+                "guess must contain at least {count} {}",
+                letter.to_ascii_uppercase()
+            ),
+            GuessError::MustMoveLetter { letter, position } => write!(
+                f,
+                "letter {} cannot be in position {position}",
+                letter.to_ascii_uppercase()
+            ),
+            GuessError::TooManyLetter { letter, count: 0 } => {
+                write!(f, "guess must not contain {}", letter.to_ascii_uppercase())
+            }
+            GuessError::TooManyLetter { letter, count } => write!(
+                f,
+                "guess must contain at most {count} {}",
+                letter.to_ascii_uppercase()
+            ),
+            GuessError::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl Error for GuessError {}
+
+/// A single round of Wordle: one answer and the guesses made against it.
+#[derive(Debug, Clone)]
+pub struct Game {
+    answer: String,
+    guesses: Vec<String>,
+    feedback: Vec<Feedback>,
+    keyboard: Keyboard,
+    status: GameStatus,
+    max_attempts: usize,
+    difficulty: Difficulty,
+    puzzle: Option<u32>,
+    started_at: DateTime<Utc>,
+    finished_at: Option<DateTime<Utc>>,
+}
+
+impl Game {
+    pub fn new(answer: &str, difficulty: Difficulty) -> Self {
+        Game {
+            answer: answer.trim().to_lowercase(),
+            guesses: Vec::new(),
+            feedback: Vec::new(),
+            keyboard: [None; 26],
+            status: GameStatus::InProgress,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            difficulty,
+            puzzle: None,
+            started_at: Utc::now(),
+            finished_at: None,
+        }
+    }
+
+    /// Sets how many guesses the player gets, e.g. 8 for an easy league.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Marks the game as a numbered daily puzzle.
+    pub fn with_puzzle(mut self, puzzle: u32) -> Self {
+        self.puzzle = Some(puzzle);
+        self
+    }
+
+    pub fn puzzle(&self) -> Option<u32> {
+        self.puzzle
+    }
+
+    pub fn answer(&self) -> &str {
+        &self.answer
+    }
+
+    pub fn word_length(&self) -> usize {
+        self.answer.chars().count()
+    }
+
+    pub fn guesses(&self) -> &[String] {
+        &self.guesses
+    }
+
+    pub fn feedback(&self) -> &[Feedback] {
+        &self.feedback
+    }
+
+    pub fn keyboard(&self) -> &Keyboard {
+        &self.keyboard
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    pub fn attempts(&self) -> usize {
+        self.guesses.len()
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    pub fn finished_at(&self) -> Option<DateTime<Utc>> {
+        self.finished_at
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.status != GameStatus::InProgress
+    }
+
+    /// Checks `guess` against the word list and hard mode rules, then scores it.
+    pub fn submit_guess(
+        &mut self,
+        guess: &str,
+        acceptable_words: &[String],
+    ) -> Result<GuessResult, GuessError> {
+        if self.is_over() {
+            return Err(GuessError::GameOver);
+        }
+
+        let guess = guess.trim().to_lowercase();
+        function::validate_guess(
+            &guess,
+            self.word_length(),
+            self.difficulty,
+            &self.guesses,
+            &self.feedback,
+            acceptable_words,
+        )?;
+
+        Ok(self.apply_guess(guess))
+    }
+
+    /// Rebuilds a finished or saved game from its record without checking
+    /// the guesses against any word list.
+    pub fn from_record(record: &GameRecord) -> Self {
+        let mut game = Game::new(&record.answer, record.difficulty.unwrap_or_default())
+            .with_max_attempts(record.max_guesses);
+        game.puzzle = record.puzzle;
+        for guess in &record.guesses {
+            if game.is_over() {
+                break;
+            }
+            game.apply_guess(guess.trim().to_lowercase());
+        }
+        if let Some(started_at) = record.started_at {
+            game.started_at = started_at;
+        }
+        if record.finished_at.is_some() {
+            game.finished_at = record.finished_at;
+        }
+        game
+    }
+
+    fn apply_guess(&mut self, guess: String) -> GuessResult {
+        let state = function::color_state(&guess, &self.answer);
+        function::keyboard_state_update(&mut self.keyboard, &guess, state);
+        self.guesses.push(guess);
+        self.feedback.push(state);
+
+        if state.is_win() {
+            self.status = GameStatus::Won;
+        } else if self.guesses.len() >= self.max_attempts {
+            self.status = GameStatus::Lost;
+        }
+        if self.is_over() {
+            self.finished_at = Some(Utc::now());
+        }
+
+        GuessResult {
+            state,
+            attempts: self.guesses.len(),
+            status: self.status,
+        }
+    }
+
+    pub fn to_record(&self) -> GameRecord {
+        GameRecord {
+            answer: self.answer.to_uppercase(),
+            guesses: self.guesses.iter().map(|g| g.to_uppercase()).collect(),
+            max_guesses: self.max_attempts,
+            puzzle: self.puzzle,
+            difficulty: Some(self.difficulty),
+            started_at: Some(self.started_at),
+            finished_at: self.finished_at,
+            outcome: Some(self.status),
+            ..GameRecord::default()
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Wordle game engine shared by the command-line frontend and other tools.
+
+pub mod builtin_words;
+pub mod cli;
+pub mod daily;
+pub mod export;
+pub mod feedback;
+pub mod function;
+pub mod game;
+pub mod migrate;
+pub mod pattern;
+pub mod share;
+pub mod solver;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_store;
+pub mod storage;
+pub mod strategy;
+
+pub use feedback::{Feedback, LetterState, ParseFeedbackError};
+pub use game::{Difficulty, Game, GameStatus, GuessError, GuessResult};
@@ -1,236 +1,185 @@
+use clap::ValueEnum;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
-use std::collections::HashMap;
+use std::cell::OnceCell;
 use std::io::{self, Write};
-
-mod builtin_words;
-mod function;
-mod solver;
+use wordle::cli::{
+    Cli, Command, ExportArgs, HistoryArgs, LeaderboardArgs, PlayerHistoryArgs, ReplayArgs,
+    ShareArgs, StatsArgs, VerifyArgs, WordList, WordsArgs,
+};
+use wordle::export::{self, ExportFilter};
+use wordle::pattern::PatternTable;
+use wordle::storage;
+use wordle::strategy::StrategyKind;
+use wordle::{Game, GameStatus, GuessError, daily, function, share, solver};
 
 /// The main function for the Wordle game, implement your own logic here
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut args = std::env::args().skip(1).peekable();
-    let mut config_path: Option<String> = None;
-    while let Some(arg) = args.peek() {
-        if *arg == "-c" || *arg == "--config" {
-            args.next();
-            config_path = Some(args.next().expect("error: missing config file path"));
-        } else {
-            args.next();
-        }
-    }
-    //second parse args
-    let mut args = std::env::args().skip(1).peekable();
-    let mut word_arg: Option<String> = None;
-    let mut day_arg: Option<u32> = None;
-    let mut seed_arg: Option<u64> = None;
-    let mut final_set_path: Option<String> = None;
-    let mut acceptable_set_path: Option<String> = None;
-    let mut state_path: Option<String> = None;
-    let default_seed: u64 = 1;
-    let mut random_mode = false;
-    let mut _diff_mode: bool = false;
-    let mut stats_mode = false;
-    let mut solver_mode = false;
-    let mut solver_only = false;
-
-    let mut successful_games: u32 = 0;
-    let mut failed_games: u32 = 0;
-    let mut total_successful_attempts: u32 = 0;
-    let mut guess_frequency: HashMap<String, u32> = HashMap::new();
-    let mut played_answers: Vec<String> = Vec::new();
-    let mut games: Vec<function::GameRecord> = Vec::new();
-
-    while let Some(arg) = args.next() {
-        if arg == "-w" || arg == "--word" {
-            if random_mode {
-                panic!("error: cannot use --word with --random");
-            }
-            word_arg = Some(args.next().expect("error"));
-        } else if arg == "-r" || arg == "--random" {
-            if word_arg.is_some() {
-                panic!("error: cannot use --random with --word");
+    let cli = Cli::from_args();
+    match cli.command {
+        None => play(cli.play),
+        Some(Command::Play(config)) => play(config),
+        Some(Command::Solve(args)) => {
+            if let Some(threads) = args.solver.threads {
+                solver::set_thread_count(threads)?;
             }
-            random_mode = true;
-        } else if arg == "-d" || arg == "--day" {
-            day_arg = Some(args.next().expect("input days").parse().expect("error"));
-        } else if arg == "-s" || arg == "--seed" {
-            seed_arg = Some(args.next().expect("input seed").parse().expect("error"));
-        } else if arg == "-D" || arg == "--difficult" {
-            _diff_mode = true;
-        } else if arg == "-t" || arg == "--stats" {
-            stats_mode = true;
-        } else if arg == "-f" || arg == "--final-set" {
-            final_set_path = Some(args.next().expect("error"));
-        } else if arg == "-a" || arg == "--acceptable-set" {
-            acceptable_set_path = Some(args.next().expect("error"));
-        } else if arg == "-S" || arg == "--state" {
-            state_path = Some(args.next().expect("error: missing state file path"));
-        } else if arg == "-v" || arg == "--solver" {
-            solver_mode = true;
-        } else if arg == "-so" || arg == "--solver-only" {
-            solver_only = true;
+            let (final_words, acceptable_words) = args.words.load()?;
+            let candidate_words = if args.all_answers {
+                &acceptable_words
+            } else {
+                &final_words
+            };
+            let table = PatternTable::load_or_build(&acceptable_words, candidate_words);
+            solver::solver_main(&table, args.solver.strategy())
         }
+        Some(Command::Stats(args)) => stats(args),
+        Some(Command::Replay(args)) => replay(args),
+        Some(Command::Leaderboard(args)) => leaderboard(args),
+        Some(Command::Export(args)) => export(args),
+        Some(Command::Share(args)) => share(args),
+        Some(Command::Verify(args)) => verify(args),
+        Some(Command::Words(args)) => words(args),
     }
+}
 
-    if solver_only {
-        let _ = solver::solver_main();
-        return Ok(());
+/// Plays games until the player stops, as configured by `config`.
+fn play(config: function::Config) -> Result<(), Box<dyn std::error::Error>> {
+    let function::Config {
+        word: word_arg,
+        random: random_mode,
+        difficult: difficulty,
+        stats: stats_mode,
+        day: day_arg,
+        seed: seed_arg,
+        words: word_sets,
+        state: state_path,
+        store: store_kind,
+        max_guesses: max_guesses_arg,
+        daily: daily_mode,
+        date: date_arg,
+        player,
+        resume,
+        solver: solver_mode,
+        solver_options,
+        share: share_mode,
+        share_file,
+        grid,
+        config: _,
+    } = config.resolve()?;
+    let default_seed: u64 = 1;
+    if let Some(threads) = solver_options.threads {
+        solver::set_thread_count(threads)?;
     }
 
+    let mut played_answers: Vec<String> = Vec::new();
+    let mut games: Vec<function::GameRecord> = Vec::new();
+
     if word_arg.is_some() && (day_arg.is_some() || seed_arg.is_some() || random_mode) {
         eprintln!("Error: Cannot use --word with --day, --seed, or --random.");
         std::process::exit(1);
     }
 
-    let mut config: function::Config = function::Config::default();
-    if let Some(path) = &config_path
-        && let Ok(file_content) = std::fs::read_to_string(path)
+    let difficulty = difficulty.unwrap_or_default();
+    let daily_mode = daily_mode || date_arg.is_some();
+    if daily_mode && (word_arg.is_some() || random_mode || day_arg.is_some() || seed_arg.is_some())
     {
-        match serde_json::from_str::<function::Config>(&file_content) {
-            Ok(loaded_config) => {
-                config = loaded_config;
-            }
-            Err(e) => {
-                eprintln!("Error parsing config file: {e}");
-                std::process::exit(1);
-            }
-        }
-    }
-
-    // second parse args
-    while let Some(arg) = args.next() {
-        if arg == "-w" || arg == "--word" {
-            word_arg = Some(args.next().expect("error"));
-        } else if arg == "-r" || arg == "--random" {
-            random_mode = true;
-        } else if arg == "-d" || arg == "--day" {
-            day_arg = Some(args.next().expect("input days").parse().expect("error"));
-        } else if arg == "-s" || arg == "--seed" {
-            seed_arg = Some(args.next().expect("input seed").parse().expect("error"));
-        } else if arg == "-D" || arg == "--difficult" {
-            _diff_mode = true;
-        } else if arg == "-t" || arg == "--stats" {
-            stats_mode = true;
-        } else if arg == "-f" || arg == "--final-set" {
-            final_set_path = Some(args.next().expect("error"));
-        } else if arg == "-a" || arg == "--acceptable-set" {
-            acceptable_set_path = Some(args.next().expect("error"));
-        } else if arg == "-S" || arg == "--state" {
-            state_path = Some(args.next().expect("error: missing state file path"));
-        } else if arg == "-v" || arg == "--solver" {
-            solver_mode = true;
-        }
-    }
-
-    if word_arg.is_none() {
-        word_arg = config.word;
-    }
-    if !random_mode {
-        random_mode = config.random.unwrap_or(false);
-    }
-    if day_arg.is_none() {
-        day_arg = config.day;
-    }
-    if seed_arg.is_none() {
-        seed_arg = config.seed;
-    }
-    if !_diff_mode {
-        _diff_mode = config.difficult.unwrap_or(false);
-    }
-    if !stats_mode {
-        stats_mode = config.stats.unwrap_or(false);
+        eprintln!("Error: Cannot use --daily with --word, --random, --day, or --seed.");
+        std::process::exit(1);
     }
-    if final_set_path.is_none() {
-        final_set_path = config.final_set;
+    let max_guesses = max_guesses_arg.unwrap_or(wordle::game::DEFAULT_MAX_ATTEMPTS);
+    if max_guesses == 0 {
+        eprintln!("error: --max-guesses must be at least 1");
+        std::process::exit(1);
     }
-    if acceptable_set_path.is_none() {
-        acceptable_set_path = config.acceptable_set;
+    if store_kind.is_some() && state_path.is_none() {
+        eprintln!("error: --store needs a state file (--state or \"state\" in the config file)");
+        std::process::exit(1);
     }
-    if state_path.is_none() {
-        state_path = config.state;
+    if resume && state_path.is_none() {
+        eprintln!("error: --resume needs a state file (--state or \"state\" in the config file)");
+        std::process::exit(1);
     }
     let is_tty = atty::is(atty::Stream::Stdout);
-    let is_answer_from_cli = word_arg.is_some();
+    let is_answer_from_cli = word_arg.is_some() || daily_mode;
     let mut current_day = day_arg.unwrap_or(1);
     let current_seed = seed_arg.unwrap_or(default_seed);
-    if current_day as usize > builtin_words::FINAL.len() {
-        eprintln!("error");
-        std::process::exit(1);
-    }
 
-    let final_words: Vec<String>;
-    let acceptable_words: Vec<String>;
+    let (final_words, acceptable_words) = word_sets.load()?;
 
-    if let Some(f_path) = final_set_path {
-        if acceptable_set_path.is_none() {
-            eprintln!("must --final-set and --acceptable-set");
-            std::process::exit(1);
-        }
-        let a_path = acceptable_set_path.unwrap();
+    if day_arg.is_some() && (current_day == 0 || current_day as usize > final_words.len()) {
+        eprintln!(
+            "error: --day must be between 1 and {} (the size of the final word list)",
+            final_words.len()
+        );
+        std::process::exit(1);
+    }
 
-        (final_words, acceptable_words) = function::load_and_validate_word_sets(&f_path, &a_path)?;
-    } else {
-        final_words = builtin_words::FINAL
-            .iter()
-            .map(|&s| s.to_string())
-            .collect();
-        acceptable_words = builtin_words::ACCEPTABLE
-            .iter()
-            .map(|&s| s.to_string())
-            .collect();
+    let word_list = function::word_list_id(&final_words);
+    let mut daily_puzzle: Option<u32> = None;
+    if daily_mode {
+        let date = match &date_arg {
+            Some(date) => daily::parse_date(date)?,
+            None => daily::today(),
+        };
+        daily_puzzle = Some(daily::puzzle_number(date)?);
     }
 
-    if let Some(path) = &state_path
-        && let Some(loaded_state) = function::load_state(path)?
+    let player = function::player_key(player.as_deref())?;
+    let store = match &state_path {
+        Some(path) => Some(storage::open(path, store_kind)?),
+        None => None,
+    };
+    let mut resumed: Option<function::GameRecord> = None;
+    if let Some(store) = &store
+        && let Some(loaded_state) = store.load()?
     {
-        games = loaded_state.games;
-        successful_games = 0;
-        failed_games = 0;
-        total_successful_attempts = 0;
-        guess_frequency = HashMap::new();
-
-        for record in &games {
-            if record
-                .guesses
-                .last()
-                .map(|g| function::color_state(g, &record.answer) == ['G', 'G', 'G', 'G', 'G'])
-                .unwrap_or(false)
-            {
-                successful_games += 1;
-                total_successful_attempts += record.guesses.len() as u32;
-            } else {
-                failed_games += 1;
-            }
-            for guess in &record.guesses {
-                *guess_frequency.entry(guess.to_lowercase()).or_insert(0) += 1;
+        let saved = loaded_state.in_progress_of(player.as_deref()).cloned();
+        games = function::player_games(loaded_state.games, player.as_deref());
+        if let Some(saved) = saved {
+            let prompt = format!(
+                "You have an unfinished game ({} guesses made). Resume it? (Y/N) ",
+                saved.guesses.len()
+            );
+            if resume || (is_tty && ask_yes_no(&prompt)?) {
+                resumed = Some(saved);
+            } else if is_tty {
+                store.save_in_progress(player.as_deref(), None)?;
             }
         }
     }
+    let final_table = OnceCell::new();
+    let full_table = OnceCell::new();
+    let is_seeded =
+        word_arg.is_none() && daily_puzzle.is_none() && (day_arg.is_some() || seed_arg.is_some());
     loop {
         let answer: String;
+        let resumed_game = resumed.take();
 
-        if let Some(word) = &word_arg {
+        if let Some(saved) = &resumed_game {
+            answer = saved.answer.to_lowercase();
+        } else if let Some(word) = &word_arg {
             if !final_words.contains(&word.trim().to_lowercase()) {
                 eprintln!("error: answer word must be in final word list");
                 std::process::exit(1);
             }
             answer = word.trim().to_lowercase();
-        } else if day_arg.is_some() || seed_arg.is_some() {
-            answer = function::get_answer_for_day(current_day, current_seed);
-        } else if random_mode {
-            loop {
-                let mut rng = thread_rng();
-                let new_answer = builtin_words::FINAL
-                    .choose(&mut rng)
-                    .expect("error")
-                    .to_string();
-                if !played_answers.contains(&new_answer) {
-                    answer = new_answer;
-                    played_answers.push(answer.clone());
-                    break;
-                }
+        } else if let Some(puzzle) = daily_puzzle {
+            if is_tty {
+                println!("Wordle #{puzzle}");
             }
+            answer = daily::answer_for_puzzle(puzzle, &final_words);
+        } else if is_seeded {
+            answer = function::get_answer_for_day(current_day, current_seed, &final_words);
+        } else if random_mode {
+            let unplayed: Vec<&String> = final_words
+                .iter()
+                .filter(|word| !played_answers.contains(word))
+                .collect();
+            let Some(&new_answer) = unplayed.choose(&mut thread_rng()) else {
+                break;
+            };
+            answer = new_answer.clone();
+            played_answers.push(answer.clone());
         } else {
             if is_tty {
                 println!("\n please input your answer:");
@@ -243,14 +192,60 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             answer = input_answer.trim().to_lowercase();
         }
 
-        let mut guess_num = 0;
-        let mut keyboard_state = ['X'; 26];
-        let mut guess_history: Vec<String> = Vec::new();
-        let mut state_history: Vec<[char; 5]> = Vec::new();
+        let mut game = match &resumed_game {
+            Some(saved) => {
+                let game = Game::from_record(saved);
+                if is_tty {
+                    print_board(&game, is_tty);
+                }
+                game
+            }
+            None => {
+                let mut game = Game::new(&answer, difficulty).with_max_attempts(max_guesses);
+                if let Some(puzzle) = daily_puzzle {
+                    game = game.with_puzzle(puzzle);
+                }
+                game
+            }
+        };
+        // typed-in answers may be outside the final list
+        let (candidate_words, pattern_table) = if final_words.contains(&answer) {
+            (&final_words, &final_table)
+        } else {
+            (&acceptable_words, &full_table)
+        };
+        // built on first use of the solver
+        let pattern_table = || {
+            pattern_table
+                .get_or_init(|| PatternTable::load_or_build(&acceptable_words, candidate_words))
+        };
+        let make_record = |game: &Game| {
+            let mut record = game.to_record();
+            record.player = player.clone();
+            match &resumed_game {
+                Some(saved) => {
+                    record.word_list = saved.word_list.clone();
+                    record.day = saved.day;
+                    record.seed = saved.seed;
+                }
+                None => {
+                    record.word_list = Some(word_list.clone());
+                    if is_seeded {
+                        record.day = Some(current_day);
+                        record.seed = Some(current_seed);
+                    }
+                }
+            }
+            record
+        };
 
         loop {
             if is_tty {
-                print!("give me your guess ({} times):", guess_num + 1);
+                print!(
+                    "give me your guess ({}/{} times):",
+                    game.attempts() + 1,
+                    game.max_attempts()
+                );
                 io::stdout().flush()?;
             }
 
@@ -259,58 +254,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 return Ok(());
             }
 
-            let trimmed_guess = guess.trim().to_lowercase();
-            if !function::is_valid(
-                &trimmed_guess,
-                _diff_mode,
-                &guess_history,
-                &state_history,
-                &acceptable_words,
-            ) {
-                println!("INVALID");
-                continue;
-            }
-            *guess_frequency.entry(trimmed_guess.clone()).or_insert(0) += 1;
-            guess_history.push(trimmed_guess.clone());
-
-            let state = function::color_state(&trimmed_guess, &answer);
-            state_history.push(state);
-
-            function::keyboard_state_update(&mut keyboard_state, &trimmed_guess, state);
-            if is_tty {
-                for i in 0..guess_history.len() {
-                    let history_guess = &guess_history[i].to_uppercase();
-                    let history_state = state_history[i];
-                    function::print_result(history_state, history_guess);
+            match game.submit_guess(&guess, &acceptable_words) {
+                Ok(_) => {}
+                Err(GuessError::GameOver) => break,
+                Err(e) => {
+                    if is_tty {
+                        println!("{}", console::style(format!("INVALID: {e}")).red());
+                    } else {
+                        println!("INVALID");
+                    }
+                    continue;
                 }
-                println!();
-                function::print_keyboard_state(&keyboard_state);
-            } else {
-                println!(
-                    "{} {}",
-                    state.iter().collect::<String>(),
-                    keyboard_state.iter().collect::<String>()
-                ); //print state 
             }
-
-            guess_num += 1;
-
-            if state == ['G', 'G', 'G', 'G', 'G'] {
-                if is_tty {
-                    println!("\nYou are right! The answer is {answer}");
-                }
-                println!("CORRECT {guess_num}");
-                successful_games += 1;
-                total_successful_attempts += guess_num;
+            print_board(&game, is_tty);
+            if game.is_over() {
+                print_outcome(&game, is_tty);
                 break;
             }
-            if guess_num >= 6 {
-                if is_tty {
-                    println!("\nYou failed ,the answer is {answer}");
-                }
-                println!("FAILED {}", answer.to_uppercase());
-                failed_games += 1;
-                break;
+            if let Some(store) = &store
+                && let Err(e) = store.save_in_progress(player.as_deref(), Some(make_record(&game)))
+            {
+                eprintln!("Error saving game state: {e}");
             }
             if solver_mode {
                 println!("Solver mode active. ");
@@ -319,51 +283,77 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 io::stdin().read_line(&mut input)?;
                 let input = input.trim().to_uppercase();
                 if input.contains("LEFT") {
-                    solver::print_remaining_words(
-                        &acceptable_words,
-                        &guess_history,
-                        &state_history,
-                    );
+                    solver::print_remaining_words(pattern_table(), game.guesses(), game.feedback());
                 }
-                if input.contains("REC") {
-                    solver::print_top_recommendations(
-                        &acceptable_words,
-                        &guess_history,
-                        &state_history,
-                    );
+                if let Some(rec) = input.find("REC").map(|i| input[i + "REC".len()..].trim()) {
+                    // "rec" may name a strategy, e.g. "rec minimax"
+                    let rec_strategy = match rec.split_whitespace().next() {
+                        Some(name) => StrategyKind::from_str(name, true).ok(),
+                        None => Some(solver_options.strategy()),
+                    };
+                    match rec_strategy {
+                        Some(rec_strategy) => solver::print_top_recommendations(
+                            pattern_table(),
+                            game.guesses(),
+                            game.feedback(),
+                            rec_strategy,
+                        ),
+                        None => println!("Unknown strategy. {}", solver::strategy_names()),
+                    }
                 }
             }
         }
 
-        let current_game = function::GameRecord {
-            answer: answer.to_uppercase(),
-            guesses: guess_history.iter().map(|g| g.to_uppercase()).collect(),
-        };
-        games.push(current_game);
-
-        if let Some(path) = &state_path {
-            let state_to_save = function::GameState {
-                total_rounds: games.len() as u32,
-                games: games.clone(),
-            };
-            if let Err(e) = function::save_state(path, &state_to_save) {
-                eprintln!("Error saving game state: {e}");
+        if share_mode || share_file.is_some() {
+            if share_mode {
+                let grid = share::share_text(&game, grid.ascii || share::wants_ascii(is_tty));
+                println!("{grid}");
             }
+            if let Some(path) = &share_file
+                && let Err(e) = std::fs::write(path, share::share_text(&game, grid.ascii))
+            {
+                eprintln!("Error writing {path}: {e}");
+            }
+        }
+
+        let record = make_record(&game);
+        match &store {
+            // picks up games other sessions saved to the same file meanwhile
+            Some(store) => match store.append_game(record.clone()) {
+                Ok(saved_state) => {
+                    games = function::player_games(saved_state.games, player.as_deref())
+                }
+                Err(e) => {
+                    eprintln!("Error saving game state: {e}");
+                    games.push(record);
+                }
+            },
+            None => games.push(record),
         }
         if stats_mode {
-            function::print_stats(
-                is_tty,
-                successful_games,
-                failed_games,
-                total_successful_attempts,
-                &guess_frequency,
-            );
+            function::print_stats(is_tty, &function::summarize_games(&games));
         }
 
         if is_answer_from_cli {
             break;
         } else {
             current_day += 1;
+            if is_seeded && current_day as usize > final_words.len() {
+                if is_tty {
+                    println!(
+                        "\nThat was day {} of {}: every answer of the final word list has been played.",
+                        current_day - 1,
+                        final_words.len()
+                    );
+                }
+                break;
+            }
+            if random_mode && played_answers.len() >= final_words.len() {
+                if is_tty {
+                    println!("\nEvery answer of the final word list has been played.");
+                }
+                break;
+            }
             if is_tty {
                 print!("\nDo you wanna play a new game ? (Y/N) ");
                 io::stdout().flush()?;
@@ -381,3 +371,328 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+fn ask_yes_no(prompt: &str) -> io::Result<bool> {
+    print!("{prompt}");
+    io::stdout().flush()?;
+    let mut choice = String::new();
+    io::stdin().read_line(&mut choice)?;
+    Ok(choice.trim().eq_ignore_ascii_case("y"))
+}
+
+/// Prints the board after the latest guess: every guess so far and the
+/// keyboard in TTY mode, or one `STATE KEYBOARD` line otherwise.
+fn print_board(game: &Game, is_tty: bool) {
+    if is_tty {
+        for (history_guess, history_state) in game.guesses().iter().zip(game.feedback()) {
+            function::print_result(*history_state, &history_guess.to_uppercase());
+        }
+        println!();
+        function::print_keyboard_state(game.keyboard());
+    } else if let Some(state) = game.feedback().last() {
+        println!(
+            "{} {}",
+            state,
+            function::keyboard_to_string(game.keyboard())
+        ); //print state 
+    }
+}
+
+fn print_outcome(game: &Game, is_tty: bool) {
+    let answer = game.answer();
+    match game.status() {
+        GameStatus::Won => {
+            if is_tty {
+                println!("\nYou are right! The answer is {answer}");
+            }
+            println!("CORRECT {}", game.attempts());
+        }
+        GameStatus::Lost => {
+            if is_tty {
+                println!("\nYou failed ,the answer is {answer}");
+            }
+            println!("FAILED {}", answer.to_uppercase());
+        }
+        GameStatus::InProgress => {}
+    }
+}
+
+/// Loads every game in the state file, of all players.
+fn load_games(
+    history: &HistoryArgs,
+) -> Result<Vec<function::GameRecord>, Box<dyn std::error::Error>> {
+    // opening an SQLite store would create the file
+    if !std::path::Path::new(&history.state).exists() {
+        return Err(format!("state file {} does not exist", history.state).into());
+    }
+    match storage::open(&history.state, history.store)?.load()? {
+        Some(state) => Ok(state.games),
+        None => Err(format!("cannot read state file {}", history.state).into()),
+    }
+}
+
+/// Loads the games of the chosen profile, see [`function::player_games`].
+fn player_games(
+    args: &PlayerHistoryArgs,
+) -> Result<Vec<function::GameRecord>, Box<dyn std::error::Error>> {
+    let player = function::player_key(args.player.as_deref())?;
+    Ok(function::player_games(
+        load_games(&args.history)?,
+        player.as_deref(),
+    ))
+}
+
+fn stats(args: StatsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let games = player_games(&args.games)?;
+    let stats = function::summarize_games(&games);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    let is_tty = atty::is(atty::Stream::Stdout);
+    function::print_stats(is_tty, &stats);
+    Ok(())
+}
+
+fn replay(args: ReplayArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let games = player_games(&args.games)?;
+    let (index, record) = select_game(&games, args.game, &args.games.history.state)?;
+
+    let is_tty = atty::is(atty::Stream::Stdout);
+    if is_tty {
+        println!("Game {} of {}", index + 1, games.len());
+    }
+    if is_tty {
+        print_board(&Game::from_record(record), is_tty);
+    } else {
+        // one line per guess, with the keyboard as it was after that guess
+        for n in 1..=record.guesses.len() {
+            let partial = function::GameRecord {
+                guesses: record.guesses[..n].to_vec(),
+                ..record.clone()
+            };
+            print_board(&Game::from_record(&partial), is_tty);
+        }
+    }
+    let game = Game::from_record(record);
+    print_outcome(&game, is_tty);
+    Ok(())
+}
+
+fn share(args: ShareArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let games = player_games(&args.games)?;
+    let (_, record) = select_game(&games, args.game, &args.games.history.state)?;
+    let game = Game::from_record(record);
+
+    match &args.output {
+        Some(path) => std::fs::write(path, share::share_text(&game, args.grid.ascii))?,
+        None => {
+            let is_tty = atty::is(atty::Stream::Stdout);
+            print!(
+                "{}",
+                share::share_text(&game, args.grid.ascii || share::wants_ascii(is_tty))
+            );
+        }
+    }
+    Ok(())
+}
+
+fn verify(args: VerifyArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let text = match &args.input {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => io::read_to_string(io::stdin())?,
+    };
+    let grids = share::parse_grids(&text)?;
+    let (final_words, acceptable_words) = args.words.load()?;
+    let is_tty = atty::is(atty::Stream::Stdout);
+
+    let shown = |words: &[String]| {
+        let limit = if args.limit == 0 {
+            words.len()
+        } else {
+            args.limit
+        };
+        let mut list: Vec<String> = words.iter().take(limit).map(|w| w.to_uppercase()).collect();
+        if words.len() > limit {
+            list.push("...".to_string());
+        }
+        list
+    };
+    let mut possible = 0;
+    let mut distribution = vec![0; wordle::game::DEFAULT_MAX_ATTEMPTS];
+    for (n, grid) in grids.iter().enumerate() {
+        let answer = match &args.answer {
+            Some(answer) => Some(answer.trim().to_lowercase()),
+            None => args
+                .puzzle
+                .or(grid.puzzle)
+                .map(|puzzle| daily::answer_for_puzzle(puzzle, &final_words)),
+        };
+        let check = share::check_grid(grid, answer.as_deref(), &acceptable_words);
+        let answer_text = answer.as_deref().unwrap_or("?").to_uppercase();
+
+        if is_tty {
+            println!("\n--- grid {} (answer {answer_text}) ---", n + 1);
+        } else {
+            let verdict = if check.is_possible() {
+                "POSSIBLE"
+            } else {
+                "IMPOSSIBLE"
+            };
+            println!("GRID {} {verdict} {answer_text}", n + 1);
+        }
+        for (i, row) in grid.rows.iter().enumerate() {
+            let words = check.candidates.get(i).map(Vec::as_slice).unwrap_or(&[]);
+            if is_tty {
+                let count = if answer.is_some() {
+                    format!("{} words: {}", words.len(), shown(words).join(", "))
+                } else {
+                    String::new()
+                };
+                println!("{:>2} {row}  {count}", i + 1);
+            } else {
+                println!("ROW {row} {} {}", words.len(), shown(words).join(" "));
+            }
+        }
+        for problem in &check.problems {
+            if is_tty {
+                println!("{}", console::style(format!("impossible: {problem}")).red());
+            } else {
+                println!("PROBLEM {problem}");
+            }
+        }
+
+        if check.is_possible() {
+            possible += 1;
+            if let Some(score) = grid.score {
+                if distribution.len() < score {
+                    distribution.resize(score, 0);
+                }
+                distribution[score - 1] += 1;
+            }
+            if is_tty {
+                println!("{}", console::style("possible").green());
+            }
+        }
+    }
+
+    if grids.len() > 1 {
+        let impossible = grids.len() - possible;
+        if is_tty {
+            println!("\n--- {possible} possible, {impossible} impossible ---");
+            function::print_distribution_chart(&distribution);
+        } else {
+            println!("SUMMARY {possible} {impossible}");
+            let counts: Vec<String> = distribution.iter().map(|c| c.to_string()).collect();
+            println!("DISTRIBUTION {}", counts.join(" "));
+        }
+    }
+    Ok(())
+}
+
+/// Picks game `number` (1 is the oldest, negative counts from the newest)
+/// and returns its 0-based index.
+fn select_game<'a>(
+    games: &'a [function::GameRecord],
+    number: i64,
+    path: &str,
+) -> Result<(usize, &'a function::GameRecord), Box<dyn std::error::Error>> {
+    let index = if number < 0 {
+        games.len() as i64 + number
+    } else {
+        number - 1
+    };
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| Some((i, games.get(i)?)))
+        .ok_or_else(|| {
+            format!(
+                "no game {number} in {path} ({} games recorded)",
+                games.len()
+            )
+            .into()
+        })
+}
+
+fn leaderboard(args: LeaderboardArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let entries = function::leaderboard(&load_games(&args.history)?);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    let is_tty = atty::is(atty::Stream::Stdout);
+    function::print_leaderboard(is_tty, &entries);
+    Ok(())
+}
+
+fn export(args: ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let filter = ExportFilter {
+        from: args.from.as_deref().map(daily::parse_date).transpose()?,
+        to: args.to.as_deref().map(daily::parse_date).transpose()?,
+        mode: args.mode,
+        difficulty: args.difficulty,
+    };
+    let player = match &args.player {
+        Some(name) => Some(function::player_key(Some(name))?),
+        None => None,
+    };
+
+    let games = load_games(&args.history)?;
+    let selected: Vec<(usize, &function::GameRecord)> = games
+        .iter()
+        .enumerate()
+        .map(|(i, record)| (i + 1, record))
+        .filter(|(_, record)| {
+            player
+                .as_ref()
+                .is_none_or(|player| &record.player == player)
+                && filter.matches(record)
+        })
+        .collect();
+
+    match &args.output {
+        Some(path) => {
+            let file = io::BufWriter::new(std::fs::File::create(path)?);
+            export::write_games(file, args.format, &selected, args.guesses)
+        }
+        None => export::write_games(io::stdout().lock(), args.format, &selected, args.guesses),
+    }
+}
+
+fn words(args: WordsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (final_words, acceptable_words) = args.words.load()?;
+
+    if let Some(list) = args.list {
+        let words = match list {
+            WordList::Final => &final_words,
+            WordList::Acceptable => &acceptable_words,
+        };
+        for word in words {
+            println!("{word}");
+        }
+    }
+
+    for query in &args.query {
+        let word = query.trim().to_lowercase();
+        let kind = if final_words.contains(&word) {
+            "final"
+        } else if acceptable_words.contains(&word) {
+            "acceptable"
+        } else {
+            "unknown"
+        };
+        println!("{} {kind}", word.to_uppercase());
+    }
+
+    if args.list.is_none() && args.query.is_empty() {
+        println!(
+            "{} final words, {} acceptable words, {} letters each",
+            final_words.len(),
+            acceptable_words.len(),
+            args.words.word_length()
+        );
+    }
+    Ok(())
+}
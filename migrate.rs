@@ -0,0 +1,238 @@
+//! Upgrades state files written by older versions to the current schema.
+//!
+//! Migrations work on the raw JSON so they do not depend on how the current
+//! structs look. To change the schema, bump [`CURRENT_SCHEMA_VERSION`] and
+//! append a step to [`MIGRATIONS`].
+
+use serde_json::{Map, Value};
+use std::error::Error;
+
+pub const CURRENT_SCHEMA_VERSION: u32 = 3;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), Box<dyn Error>>;
+
+/// `MIGRATIONS[i]` upgrades a file from version `i + 1` to `i + 2`.
+const MIGRATIONS: &[Migration] = &[v1_to_v2, v2_to_v3];
+
+/// Version of a state file; files without a `schema_version` are version 1.
+pub fn schema_version(state: &Value) -> Result<u32, Box<dyn Error>> {
+    match state.get("schema_version") {
+        None => Ok(1),
+        Some(version) => version
+            .as_u64()
+            .map(|v| v as u32)
+            .ok_or_else(|| "schema_version must be a number".into()),
+    }
+}
+
+/// Runs every migration needed to bring `state` to the current version.
+/// Returns the version the file had before.
+pub fn migrate(state: &mut Value) -> Result<u32, Box<dyn Error>> {
+    let version = schema_version(state)?;
+    if version == 0 || version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "state file has schema version {version}, this program supports up to {CURRENT_SCHEMA_VERSION}"
+        )
+        .into());
+    }
+
+    let Value::Object(map) = state else {
+        return Err("state file must contain a JSON object".into());
+    };
+    for (from, step) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        step(map).map_err(|e| format!("migrating state from version {}: {e}", from + 1))?;
+        map.insert("schema_version".to_string(), Value::from(from as u32 + 2));
+    }
+    Ok(version)
+}
+
+/// Version 1 had no version field and records held only the answer and the
+/// guesses. Fill in the attempt limit and the outcome, and recount rounds.
+fn v1_to_v2(state: &mut Map<String, Value>) -> Result<(), Box<dyn Error>> {
+    let games = state
+        .entry("games")
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or("games must be an array")?;
+
+    for game in games.iter_mut() {
+        let record = game.as_object_mut().ok_or("each game must be an object")?;
+        record
+            .entry("max_guesses")
+            .or_insert_with(|| Value::from(crate::game::DEFAULT_MAX_ATTEMPTS));
+
+        if !record.contains_key("outcome") {
+            let answer = record.get("answer").and_then(Value::as_str).unwrap_or("");
+            let won = record
+                .get("guesses")
+                .and_then(Value::as_array)
+                .and_then(|guesses| guesses.last())
+                .and_then(Value::as_str)
+                .is_some_and(|last| last.eq_ignore_ascii_case(answer));
+            record.insert(
+                "outcome".to_string(),
+                Value::from(if won { "won" } else { "lost" }),
+            );
+        }
+    }
+
+    let rounds = games.len();
+    state.insert("total_rounds".to_string(), Value::from(rounds));
+    Ok(())
+}
+
+/// Version 2 had a single unfinished game; with player profiles there is
+/// one per player, so `in_progress` becomes a list.
+fn v2_to_v3(state: &mut Map<String, Value>) -> Result<(), Box<dyn Error>> {
+    match state.remove("in_progress") {
+        None | Some(Value::Null) => {}
+        Some(record @ Value::Object(_)) => {
+            state.insert("in_progress".to_string(), Value::Array(vec![record]));
+        }
+        Some(_) => return Err("in_progress must be an object".into()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_json_diff::assert_json_eq;
+    use serde_json::json;
+
+    #[test]
+    fn migrates_v1_to_current() {
+        let mut state = json!({
+            "total_rounds": 7,
+            "games": [
+                { "answer": "TAPIR", "guesses": ["CRANE", "TAPIR"] },
+                { "answer": "TAPIR", "guesses": ["CRANE"] },
+                { "answer": "HELLO", "guesses": ["hello"], "max_guesses": 3, "outcome": "lost" }
+            ]
+        });
+        assert_eq!(migrate(&mut state).unwrap(), 1);
+        assert_json_eq!(
+            state,
+            json!({
+                "schema_version": CURRENT_SCHEMA_VERSION,
+                "total_rounds": 3,
+                "games": [
+                    { "answer": "TAPIR", "guesses": ["CRANE", "TAPIR"], "max_guesses": 6, "outcome": "won" },
+                    { "answer": "TAPIR", "guesses": ["CRANE"], "max_guesses": 6, "outcome": "lost" },
+                    { "answer": "HELLO", "guesses": ["hello"], "max_guesses": 3, "outcome": "lost" }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn migrates_v1_without_games() {
+        let mut state = json!({});
+        migrate(&mut state).unwrap();
+        assert_json_eq!(
+            state,
+            json!({ "schema_version": CURRENT_SCHEMA_VERSION, "total_rounds": 0, "games": [] })
+        );
+    }
+
+    #[test]
+    fn migrates_v2_in_progress_to_a_list() {
+        let game = json!({ "answer": "TAPIR", "guesses": ["CRANE"], "max_guesses": 6 });
+        let mut state = json!({
+            "schema_version": 2,
+            "total_rounds": 0,
+            "games": [],
+            "in_progress": game
+        });
+        assert_eq!(migrate(&mut state).unwrap(), 2);
+        assert_json_eq!(
+            state,
+            json!({
+                "schema_version": CURRENT_SCHEMA_VERSION,
+                "total_rounds": 0,
+                "games": [],
+                "in_progress": [game]
+            })
+        );
+    }
+
+    #[test]
+    fn migrates_v2_without_in_progress() {
+        let mut state =
+            json!({ "schema_version": 2, "total_rounds": 0, "games": [], "in_progress": null });
+        migrate(&mut state).unwrap();
+        assert_json_eq!(
+            state,
+            json!({ "schema_version": CURRENT_SCHEMA_VERSION, "total_rounds": 0, "games": [] })
+        );
+    }
+
+    #[test]
+    fn leaves_current_version_alone() {
+        let original = json!({
+            "schema_version": CURRENT_SCHEMA_VERSION,
+            "total_rounds": 0,
+            "games": [],
+            "in_progress": [{ "answer": "TAPIR", "guesses": [] }]
+        });
+        let mut state = original.clone();
+        assert_eq!(migrate(&mut state).unwrap(), CURRENT_SCHEMA_VERSION);
+        assert_json_eq!(state, original);
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        for version in [0, CURRENT_SCHEMA_VERSION + 1] {
+            let mut state = json!({ "schema_version": version, "games": [] });
+            let error = migrate(&mut state).unwrap_err().to_string();
+            assert!(error.contains("supports up to"), "{error}");
+        }
+        let mut state = json!({ "schema_version": "2" });
+        assert!(migrate(&mut state).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_v2_in_progress() {
+        let mut state = json!({ "schema_version": 2, "in_progress": [] });
+        let error = migrate(&mut state).unwrap_err().to_string();
+        assert!(error.contains("from version 2"), "{error}");
+    }
+
+    #[test]
+    fn loading_an_old_file_keeps_a_backup() {
+        let dir = std::env::temp_dir().join(format!("wordle-migrate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let original = r#"{"games":[{"answer":"TAPIR","guesses":["CRANE","TAPIR"]}]}"#;
+        std::fs::write(&path, original).unwrap();
+
+        let state = crate::function::load_state(path.to_str().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(state.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(state.games.len(), 1);
+
+        let backups: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| name.starts_with("state.json.v1-") && name.ends_with(".bak"))
+            .collect();
+        assert_eq!(backups.len(), 1, "{backups:?}");
+        assert_eq!(
+            std::fs::read_to_string(dir.join(&backups[0])).unwrap(),
+            original
+        );
+        let rewritten: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_json_eq!(
+            rewritten,
+            json!({
+                "schema_version": CURRENT_SCHEMA_VERSION,
+                "total_rounds": 1,
+                "games": [{ "answer": "TAPIR", "guesses": ["CRANE", "TAPIR"], "max_guesses": 6, "outcome": "won" }]
+            })
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,362 @@
+//! Precomputed feedback of every guess against every candidate answer, so
+//! the solver looks patterns up instead of scoring words again on each
+//! `rec` or `left`.
+//!
+//! Patterns are stored as their [`Feedback::index`] in one byte for words
+//! of up to [`MAX_BYTE_CODE_LENGTH`] letters and in two bytes for longer
+//! ones. Built tables are cached on disk, keyed by the hashes of both word
+//! lists.
+
+use crate::feedback::Feedback;
+use crate::function;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Longest words whose `3^n` patterns fit in a `u8`; up to
+/// [`crate::feedback::MAX_WORD_LENGTH`] they fit in a `u16`.
+pub const MAX_BYTE_CODE_LENGTH: usize = 5;
+
+/// Start of every cache file, followed by the format version.
+const CACHE_MAGIC: &[u8; 4] = b"WPAT";
+const CACHE_VERSION: u32 = 2;
+
+pub struct PatternTable {
+    guesses: Vec<String>,
+    answers: Vec<String>,
+    guess_index: HashMap<String, usize>,
+    /// Row-major, one row of `answers.len()` codes per guess; `None` when
+    /// there are no answers.
+    codes: Option<Codes>,
+}
+
+enum Codes {
+    Byte(Vec<u8>),
+    Wide(Vec<u16>),
+}
+
+impl Codes {
+    /// Bytes per code for words of `word_length`.
+    fn width(word_length: usize) -> usize {
+        if word_length <= MAX_BYTE_CODE_LENGTH {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Little-endian codes as in the cache file.
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Codes::Byte(codes) => codes.clone(),
+            Codes::Wide(codes) => codes.iter().flat_map(|code| code.to_le_bytes()).collect(),
+        }
+    }
+
+    fn from_bytes(bytes: &[u8], width: usize) -> Self {
+        match width {
+            1 => Codes::Byte(bytes.to_vec()),
+            _ => Codes::Wide(
+                bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn all_below(&self, pattern_count: usize) -> bool {
+        match self {
+            Codes::Byte(codes) => codes.iter().all(|&code| (code as usize) < pattern_count),
+            Codes::Wide(codes) => codes.iter().all(|&code| (code as usize) < pattern_count),
+        }
+    }
+}
+
+impl PatternTable {
+    /// Computes the table for `guesses` × `answers`.
+    pub fn build(guesses: &[String], answers: &[String]) -> Self {
+        let mut table = PatternTable::without_codes(guesses, answers);
+        if answers.is_empty() {
+            return table;
+        }
+        table.codes = Some(if Codes::width(table.word_length()) == 1 {
+            Codes::Byte(compute_codes(guesses, answers, |index| index as u8))
+        } else {
+            Codes::Wide(compute_codes(guesses, answers, |index| index as u16))
+        });
+        table
+    }
+
+    /// Loads the table from the cache directory, or builds and caches it.
+    /// Cache problems are not fatal: the table is then just rebuilt.
+    pub fn load_or_build(guesses: &[String], answers: &[String]) -> Self {
+        let Some(path) = cache_path(guesses, answers) else {
+            return PatternTable::build(guesses, answers);
+        };
+        let mut table = PatternTable::without_codes(guesses, answers);
+        if let Some(codes) = read_cache(&path, guesses.len(), answers.len(), table.word_length()) {
+            table.codes = Some(codes);
+            return table;
+        }
+
+        let table = PatternTable::build(guesses, answers);
+        if let Some(codes) = &table.codes
+            && let Err(e) = write_cache(&path, guesses.len(), answers.len(), &codes.to_bytes())
+        {
+            eprintln!("Could not cache pattern table in {}: {e}", path.display());
+        }
+        table
+    }
+
+    fn without_codes(guesses: &[String], answers: &[String]) -> Self {
+        PatternTable {
+            guesses: guesses.to_vec(),
+            answers: answers.to_vec(),
+            guess_index: guesses
+                .iter()
+                .enumerate()
+                .map(|(i, word)| (word.clone(), i))
+                .collect(),
+            codes: None,
+        }
+    }
+
+    pub fn word_length(&self) -> usize {
+        self.answers
+            .first()
+            .or(self.guesses.first())
+            .map_or(0, |word| word.chars().count())
+    }
+
+    pub fn guesses(&self) -> &[String] {
+        &self.guesses
+    }
+
+    pub fn answers(&self) -> &[String] {
+        &self.answers
+    }
+
+    pub fn guess_index(&self, word: &str) -> Option<usize> {
+        self.guess_index.get(word).copied()
+    }
+
+    /// Pattern of guess number `guess` against answer number `answer`, as
+    /// given by [`crate::feedback::Feedback::index`].
+    pub fn pattern(&self, guess: usize, answer: usize) -> u32 {
+        let position = guess * self.answers.len() + answer;
+        match &self.codes {
+            Some(Codes::Byte(codes)) => codes[position] as u32,
+            Some(Codes::Wide(codes)) => codes[position] as u32,
+            None => function::color_state(&self.guesses[guess], &self.answers[answer]).index(),
+        }
+    }
+}
+
+/// `$WORDLE_CACHE_DIR`, else `wordle` in `$XDG_CACHE_HOME` or `~/.cache`.
+fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("WORDLE_CACHE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    let base = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    };
+    Some(base.join("wordle"))
+}
+
+fn cache_path(guesses: &[String], answers: &[String]) -> Option<PathBuf> {
+    Some(cache_dir()?.join(format!(
+        "patterns-{:016x}-{:016x}.bin",
+        function::word_list_hash(guesses),
+        function::word_list_hash(answers)
+    )))
+}
+
+fn header(guess_count: usize, answer_count: usize) -> Vec<u8> {
+    let mut header = CACHE_MAGIC.to_vec();
+    for value in [CACHE_VERSION, guess_count as u32, answer_count as u32] {
+        header.extend_from_slice(&value.to_le_bytes());
+    }
+    header
+}
+
+/// Computes every code of the table, `encode` narrowing each
+/// [`Feedback::index`].
+fn compute_codes<T: Copy + Default + Send>(
+    guesses: &[String],
+    answers: &[String],
+    encode: impl Fn(u32) -> T + Sync,
+) -> Vec<T> {
+    let mut codes = vec![T::default(); guesses.len() * answers.len()];
+    codes
+        .par_chunks_mut(answers.len())
+        .zip(guesses)
+        .for_each(|(row, guess)| {
+            for (code, answer) in row.iter_mut().zip(answers) {
+                *code = encode(function::color_state(guess, answer).index());
+            }
+        });
+    codes
+}
+
+/// The cached codes, or `None` if the file is missing, does not match or
+/// holds codes that are no pattern of `word_length` letters.
+fn read_cache(
+    path: &Path,
+    guess_count: usize,
+    answer_count: usize,
+    word_length: usize,
+) -> Option<Codes> {
+    let data = fs::read(path).ok()?;
+    let header = header(guess_count, answer_count);
+    let width = Codes::width(word_length);
+    if !data.starts_with(&header) || data.len() != header.len() + guess_count * answer_count * width
+    {
+        return None;
+    }
+    let codes = Codes::from_bytes(&data[header.len()..], width);
+    codes
+        .all_below(Feedback::pattern_count(word_length))
+        .then_some(codes)
+}
+
+fn write_cache(
+    path: &Path,
+    guess_count: usize,
+    answer_count: usize,
+    codes: &[u8],
+) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut data = header(guess_count, answer_count);
+    data.extend_from_slice(codes);
+    // written aside and renamed so readers never see half a table
+    let tmp_path = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp_path, data)?;
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Once;
+
+    /// Points the cache at a directory of its own. Every test uses other
+    /// word lists, so they can share it.
+    fn use_temp_cache() {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            let dir = std::env::temp_dir().join(format!("wordle-cache-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            // SAFETY: set once, before any test of this module reads it,
+            // and no other test reads WORDLE_CACHE_DIR
+            unsafe { std::env::set_var("WORDLE_CACHE_DIR", dir) };
+        });
+    }
+
+    /// Every word of `length` letters over `letters`, in order.
+    fn words(letters: &str, length: usize) -> Vec<String> {
+        let mut words = vec![String::new()];
+        for _ in 0..length {
+            words = words
+                .iter()
+                .flat_map(|word| letters.chars().map(move |c| format!("{word}{c}")))
+                .collect();
+        }
+        words
+    }
+
+    fn assert_matches_feedback(table: &PatternTable) {
+        for (g, guess) in table.guesses().iter().enumerate() {
+            for (a, answer) in table.answers().iter().enumerate() {
+                assert_eq!(
+                    table.pattern(g, a),
+                    Feedback::compute(guess, answer).index(),
+                    "{guess} against {answer}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn patterns_match_feedback() {
+        for (letters, length) in [("abc", 1), ("abcd", 5), ("ab", 7), ("ab", 10)] {
+            let guesses = words(letters, length);
+            let answers: Vec<String> = guesses.iter().step_by(3).cloned().collect();
+            let table = PatternTable::build(&guesses, &answers);
+            assert_eq!(table.word_length(), length);
+            assert_matches_feedback(&table);
+        }
+    }
+
+    #[test]
+    fn wide_tables_round_trip_through_the_cache() {
+        use_temp_cache();
+        let guesses = words("aeiou", 6)[..400].to_vec();
+        let answers = guesses[..150].to_vec();
+        let path = cache_path(&guesses, &answers).unwrap();
+        let _ = fs::remove_file(&path);
+
+        let built = PatternTable::load_or_build(&guesses, &answers);
+        assert_eq!(
+            fs::metadata(&path).unwrap().len() as usize,
+            header(guesses.len(), answers.len()).len() + 2 * guesses.len() * answers.len()
+        );
+        let cached = read_cache(&path, guesses.len(), answers.len(), 6).unwrap();
+        assert!(matches!(cached, Codes::Wide(_)));
+
+        let loaded = PatternTable::load_or_build(&guesses, &answers);
+        assert_matches_feedback(&loaded);
+        for g in 0..guesses.len() {
+            for a in 0..answers.len() {
+                assert_eq!(loaded.pattern(g, a), built.pattern(g, a));
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_caches_are_rebuilt() {
+        use_temp_cache();
+        let guesses = words("bcdef", 5)[..300].to_vec();
+        let answers = guesses[100..200].to_vec();
+        let path = cache_path(&guesses, &answers).unwrap();
+        PatternTable::load_or_build(&guesses, &answers);
+        let full_length = fs::metadata(&path).unwrap().len();
+
+        let data = fs::read(&path).unwrap();
+        fs::write(&path, &data[..data.len() - 1]).unwrap();
+        assert!(read_cache(&path, guesses.len(), answers.len(), 5).is_none());
+
+        let table = PatternTable::load_or_build(&guesses, &answers);
+        assert_matches_feedback(&table);
+        assert_eq!(fs::metadata(&path).unwrap().len(), full_length);
+    }
+
+    #[test]
+    fn caches_with_impossible_codes_are_rebuilt() {
+        use_temp_cache();
+        for (letters, length, bad_code) in [("ghijk", 5, [243u8, 0]), ("lmn", 7, [0xff, 0xff])] {
+            let guesses = words(letters, length)[..200].to_vec();
+            let answers = guesses[..50].to_vec();
+            let path = cache_path(&guesses, &answers).unwrap();
+            PatternTable::load_or_build(&guesses, &answers);
+            let mut data = fs::read(&path).unwrap();
+            let code = header(guesses.len(), answers.len()).len() + 2 * 77;
+            data[code..code + 2].copy_from_slice(&bad_code);
+            fs::write(&path, &data).unwrap();
+            assert!(read_cache(&path, guesses.len(), answers.len(), length).is_none());
+
+            let table = PatternTable::load_or_build(&guesses, &answers);
+            assert_matches_feedback(&table);
+            assert!(read_cache(&path, guesses.len(), answers.len(), length).is_some());
+        }
+    }
+}
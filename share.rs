@@ -0,0 +1,424 @@
+//! Spoiler-free result grid to share a finished game, and reading such
+//! grids back to check them.
+
+use crate::feedback::{Feedback, LetterState};
+use crate::game::{Difficulty, Game, GameStatus};
+use crate::solver;
+use std::error::Error;
+
+/// Header and one row of squares per guess, e.g.
+///
+/// ```text
+/// Wordle 123 4/6*
+///
+/// ⬛🟨⬛⬛⬛
+/// ...
+/// ```
+///
+/// The puzzle number is only shown for daily games, `X` replaces the guess
+/// count of a lost game and `*` marks hard or strict mode. With `ascii` the
+/// squares are the `G`/`Y`/`R` letters of the feedback protocol.
+pub fn share_text(game: &Game, ascii: bool) -> String {
+    let mut header = "Wordle".to_string();
+    if let Some(puzzle) = game.puzzle() {
+        header.push_str(&format!(" {puzzle}"));
+    }
+    let score = match game.status() {
+        GameStatus::Won => game.attempts().to_string(),
+        GameStatus::Lost | GameStatus::InProgress => "X".to_string(),
+    };
+    header.push_str(&format!(" {score}/{}", game.max_attempts()));
+    if game.difficulty() != Difficulty::Normal {
+        header.push('*');
+    }
+
+    let mut text = header + "\n";
+    for feedback in game.feedback() {
+        text.push('\n');
+        for &state in feedback.states() {
+            text.push_str(square(state, ascii));
+        }
+    }
+    text.push('\n');
+    text
+}
+
+fn square(state: LetterState, ascii: bool) -> &'static str {
+    match (state, ascii) {
+        (LetterState::Correct, false) => "🟩",
+        (LetterState::Present, false) => "🟨",
+        (LetterState::Absent, false) => "⬛",
+        (LetterState::Correct, true) => "G",
+        (LetterState::Present, true) => "Y",
+        (LetterState::Absent, true) => "R",
+    }
+}
+
+/// Whether emoji can be printed: terminals whose locale is not UTF-8 get
+/// the ASCII grid, while pipes and files always get emoji.
+pub fn wants_ascii(is_tty: bool) -> bool {
+    is_tty && !console::Term::stdout().features().wants_emoji()
+}
+
+/// A result grid as pasted by a player, see [`share_text`].
+#[derive(Debug, Clone)]
+pub struct SharedGrid {
+    pub puzzle: Option<u32>,
+    /// Guesses used, `None` for a lost (`X`) game.
+    pub score: Option<usize>,
+    pub max_guesses: usize,
+    /// Marked with `*`: hard or strict mode.
+    pub hard: bool,
+    pub rows: Vec<Feedback>,
+}
+
+/// Reads every grid in `text`. Each starts at a `Wordle ... N/M` header
+/// line and takes the square rows below it; other lines are ignored, so
+/// whole chat messages can be pasted.
+///
+/// Besides the 🟩🟨⬛ squares, ⬜ (light theme), 🟧🟦 (high contrast) and
+/// the ASCII `G`/`Y`/`R` rows are understood.
+pub fn parse_grids(text: &str) -> Result<Vec<SharedGrid>, Box<dyn Error>> {
+    let mut grids: Vec<SharedGrid> = Vec::new();
+    // whether the last grid can still take rows
+    let mut open = false;
+    for line in text.lines().map(str::trim) {
+        if is_header(line) {
+            grids.push(parse_header(line)?);
+            open = true;
+            continue;
+        }
+        let Some(grid) = grids.last_mut().filter(|_| open) else {
+            continue;
+        };
+        match parse_row(line) {
+            Some(row) => grid.rows.push(row?),
+            // blank lines or text may come between the header and the rows
+            None if grid.rows.is_empty() => {}
+            None => open = false,
+        }
+    }
+
+    if grids.is_empty() {
+        return Err("no 'Wordle N/M' header found".into());
+    }
+    Ok(grids)
+}
+
+/// A line starting with `Wordle` that has a score like `4/6*`; other lines
+/// mentioning Wordle are chat.
+fn is_header(line: &str) -> bool {
+    let mut tokens = line.split_whitespace();
+    tokens.next() == Some("Wordle") && tokens.any(|token| parse_score(token).is_some())
+}
+
+/// Guesses used (`None` for `X`), guesses allowed and the hard-mode star
+/// of a `4/6*` score.
+fn parse_score(token: &str) -> Option<(Option<usize>, usize, bool)> {
+    let (score, max) = token.split_once('/')?;
+    let (max, hard) = match max.strip_suffix('*') {
+        Some(max) => (max, true),
+        None => (max, false),
+    };
+    let score = match score {
+        "X" | "x" => None,
+        _ => Some(score.parse().ok()?),
+    };
+    Some((score, max.parse().ok()?, hard))
+}
+
+fn parse_header(line: &str) -> Result<SharedGrid, Box<dyn Error>> {
+    let invalid = || format!("invalid grid header '{line}', expected e.g. 'Wordle 123 4/6*'");
+    let mut grid = SharedGrid {
+        puzzle: None,
+        score: None,
+        max_guesses: 0,
+        hard: false,
+        rows: Vec::new(),
+    };
+    for token in line.split_whitespace().skip(1) {
+        match token.split_once('/') {
+            Some(_) => {
+                let (score, max_guesses, hard) = parse_score(token).ok_or_else(invalid)?;
+                grid.score = score;
+                grid.max_guesses = max_guesses;
+                grid.hard = hard;
+            }
+            // puzzle numbers are shown as "1,234" or "#1234" by some clients
+            None => {
+                let number: String = token.chars().filter(|&c| c != ',' && c != '#').collect();
+                grid.puzzle = Some(number.parse().map_err(|_| invalid())?);
+            }
+        }
+    }
+    if grid.max_guesses == 0 {
+        return Err(invalid().into());
+    }
+    Ok(grid)
+}
+
+/// `None` if `line` is not a row of squares.
+fn parse_row(line: &str) -> Option<Result<Feedback, Box<dyn Error>>> {
+    if line.is_empty() {
+        return None;
+    }
+    let mut letters = String::new();
+    // emoji may carry a variation selector
+    for c in line.chars().filter(|&c| c != '\u{fe0f}') {
+        letters.push(match c {
+            '🟩' | '🟧' | 'G' => 'G',
+            '🟨' | '🟦' | 'Y' => 'Y',
+            '⬛' | '⬜' | 'R' => 'R',
+            _ => return None,
+        });
+    }
+    Some(
+        letters
+            .parse()
+            .map_err(|e| format!("row '{line}': {e}").into()),
+    )
+}
+
+/// Result of [`check_grid`].
+#[derive(Debug, Clone, Default)]
+pub struct GridCheck {
+    /// Per row, the words that give its feedback; empty without an answer.
+    pub candidates: Vec<Vec<String>>,
+    /// Why the grid cannot come from a real game; empty if it can.
+    pub problems: Vec<String>,
+}
+
+impl GridCheck {
+    pub fn is_possible(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Checks that `grid` could come from a real game: the header matches the
+/// rows, hard-mode grids keep their hints, and with a known `answer`, every
+/// row is the feedback of some word in `acceptable_words`.
+pub fn check_grid(
+    grid: &SharedGrid,
+    answer: Option<&str>,
+    acceptable_words: &[String],
+) -> GridCheck {
+    let mut check = GridCheck::default();
+    let problems = &mut check.problems;
+    let rows = &grid.rows;
+    if rows.is_empty() {
+        problems.push("the grid has no rows".to_string());
+        return check;
+    }
+    if rows.iter().any(|row| row.len() != rows[0].len()) {
+        problems.push("rows have different lengths".to_string());
+        return check;
+    }
+
+    if rows.len() > grid.max_guesses {
+        problems.push(format!(
+            "{} rows but only {} guesses allowed",
+            rows.len(),
+            grid.max_guesses
+        ));
+    }
+    let won = rows.last().is_some_and(Feedback::is_win);
+    match grid.score {
+        Some(score) if score != rows.len() => problems.push(format!(
+            "the header says {score} guesses but the grid has {} rows",
+            rows.len()
+        )),
+        Some(_) if !won => problems.push("won, but the last row is not all green".to_string()),
+        None if won => problems.push("marked X, but the last row is all green".to_string()),
+        None if rows.len() < grid.max_guesses => problems.push(format!(
+            "marked X after {} of {} guesses",
+            rows.len(),
+            grid.max_guesses
+        )),
+        _ => {}
+    }
+    if let Some(i) = rows[..rows.len() - 1].iter().position(Feedback::is_win) {
+        problems.push(format!("row {} is all green but the game went on", i + 1));
+    }
+
+    if grid.hard {
+        // a hint has to be reused, so it shows up again in the next row
+        for (i, pair) in rows.windows(2).enumerate() {
+            let (before, after) = (pair[0].states(), pair[1].states());
+            let kept_greens = before
+                .iter()
+                .zip(after)
+                .all(|(b, a)| *b != LetterState::Correct || *a == LetterState::Correct);
+            let hints = |states: &[LetterState]| {
+                states.iter().filter(|&&s| s != LetterState::Absent).count()
+            };
+            if !kept_greens || hints(after) < hints(before) {
+                problems.push(format!(
+                    "row {} drops a hint from row {}, which hard mode does not allow",
+                    i + 2,
+                    i + 1
+                ));
+            }
+        }
+    }
+
+    let Some(answer) = answer else {
+        return check;
+    };
+    let answer = answer.trim().to_lowercase();
+    if answer.chars().count() != rows[0].len() {
+        problems.push(format!(
+            "rows have {} letters but the answer {} has {}",
+            rows[0].len(),
+            answer.to_uppercase(),
+            answer.chars().count()
+        ));
+        return check;
+    }
+    for (i, row) in rows.iter().enumerate() {
+        let words = solver::find_possible_guesses(acceptable_words, &answer, row);
+        if words.is_empty() {
+            problems.push(format!(
+                "no word gives row {} ({row}) for {}",
+                i + 1,
+                answer.to_uppercase()
+            ));
+        }
+        check.candidates.push(words);
+    }
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn rows(grid: &SharedGrid) -> Vec<String> {
+        grid.rows.iter().map(Feedback::to_string).collect()
+    }
+
+    fn grid(text: &str) -> SharedGrid {
+        let mut grids = parse_grids(text).unwrap();
+        assert_eq!(grids.len(), 1);
+        grids.remove(0)
+    }
+
+    #[test]
+    fn skips_chat_around_the_grid() {
+        let grid = grid(
+            "Wordle is great\n\
+             Wordle 1,234 3/6*\n\
+             \n\
+             ⬛🟨⬛⬛⬛\n\
+             🟩🟩⬛⬛🟩\n\
+             🟩🟩🟩🟩🟩\n\
+             see you tomorrow",
+        );
+        assert_eq!(grid.puzzle, Some(1234));
+        assert_eq!(grid.score, Some(3));
+        assert_eq!(grid.max_guesses, 6);
+        assert!(grid.hard);
+        assert_eq!(rows(&grid), ["RYRRR", "GGRRG", "GGGGG"]);
+    }
+
+    #[test]
+    fn reads_several_grids() {
+        let grids = parse_grids("Wordle 1 1/6\nGGGGG\n\nWordle #2 2/6\nRRRRR\nGGGGG\n").unwrap();
+        assert_eq!(grids.len(), 2);
+        assert_eq!(grids[1].puzzle, Some(2));
+        assert_eq!(rows(&grids[1]), ["RRRRR", "GGGGG"]);
+    }
+
+    #[test]
+    fn ignores_variation_selectors() {
+        let grid = grid("Wordle 5 1/6\n🟩\u{fe0f}🟩🟩🟩\u{fe0f}🟩\n");
+        assert_eq!(rows(&grid), ["GGGGG"]);
+    }
+
+    #[test]
+    fn reads_other_squares_and_ascii_rows() {
+        let grid = grid("Wordle 3/6\n⬜🟦⬜⬜⬜\nGYRRR\n🟧🟧🟧🟧🟧\n");
+        assert_eq!(grid.puzzle, None);
+        assert_eq!(rows(&grid), ["RYRRR", "GYRRR", "GGGGG"]);
+    }
+
+    #[test]
+    fn reads_lost_games() {
+        let grid = grid("Wordle 7 X/2\nRRRRR\nGRRRR\n");
+        assert_eq!(grid.score, None);
+        assert!(check_grid(&grid, None, &[]).is_possible());
+    }
+
+    #[test]
+    fn rejects_text_without_a_grid() {
+        assert!(parse_grids("Wordle is great").is_err());
+        assert!(parse_grids("Wordle 12 4/six\nGGGGG").is_err());
+    }
+
+    fn problems(text: &str) -> Vec<String> {
+        check_grid(&grid(text), None, &[]).problems
+    }
+
+    #[test]
+    fn reports_a_score_that_does_not_match_the_rows() {
+        assert_eq!(
+            problems("Wordle 4/6\nRRRRR\nGGGGG\n"),
+            ["the header says 4 guesses but the grid has 2 rows"]
+        );
+        assert_eq!(
+            problems("Wordle 2/6\nRRRRR\nGRRRR\n"),
+            ["won, but the last row is not all green"]
+        );
+        assert_eq!(
+            problems("Wordle X/6\nRRRRR\nGGGGG\n"),
+            ["marked X, but the last row is all green"]
+        );
+        assert_eq!(
+            problems("Wordle X/6\nRRRRR\n"),
+            ["marked X after 1 of 6 guesses"]
+        );
+        assert_eq!(
+            problems("Wordle 3/2\nRRRRR\nRRRRR\nGGGGG\n"),
+            ["3 rows but only 2 guesses allowed"]
+        );
+    }
+
+    #[test]
+    fn reports_an_all_green_row_before_the_end() {
+        assert_eq!(
+            problems("Wordle 3/6\nGGGGG\nRRRRR\nGGGGG\n"),
+            ["row 1 is all green but the game went on"]
+        );
+    }
+
+    #[test]
+    fn reports_a_dropped_hard_mode_hint() {
+        assert_eq!(
+            problems("Wordle 3/6*\nGYRRR\nRYYRR\nGGGGG\n"),
+            ["row 2 drops a hint from row 1, which hard mode does not allow"]
+        );
+        assert_eq!(
+            problems("Wordle 3/6*\nRYYRR\nRYRRR\nGGGGG\n"),
+            ["row 2 drops a hint from row 1, which hard mode does not allow"]
+        );
+        // the same grid is fine in normal mode
+        assert!(problems("Wordle 3/6\nGYRRR\nRYYRR\nGGGGG\n").is_empty());
+    }
+
+    #[test]
+    fn lists_the_guesses_for_each_row() {
+        let words: Vec<String> = ["crane", "tapir", "ratio", "cigar"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        let won = grid("Wordle 2/6\nRYYRR\nGGGGG\n");
+        let check = check_grid(&won, Some("TAPIR"), &words);
+        assert!(check.is_possible());
+        assert_eq!(check.candidates, [vec!["crane"], vec!["tapir"]]);
+
+        // only an anagram of TAPIR could give all yellow
+        let check = check_grid(&grid("Wordle 2/6\nYYYYY\nGGGGG\n"), Some("tapir"), &words);
+        assert_eq!(check.problems, ["no word gives row 1 (YYYYY) for TAPIR"]);
+        assert_eq!(check.candidates, [vec![], vec!["tapir"]]);
+    }
+}
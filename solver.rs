@@ -1,37 +1,67 @@
-use crate::builtin_words;
+use crate::feedback::Feedback;
 use crate::function;
+use crate::pattern::PatternTable;
+use crate::strategy::StrategyKind;
+use clap::ValueEnum;
 use console::{self, style};
-use std::collections::HashSet;
 use std::io::{self, Write};
 
+/// The candidate answers of `table` that could still be the answer,
+/// sorted. The candidates are normally the final word list.
 pub fn find_remaining_words(
-    acceptable_words: &[String],
+    table: &PatternTable,
     guess_history: &[String],
-    state_history: &[[char; 5]],
+    state_history: &[Feedback],
 ) -> Vec<String> {
-    let mut remaining_words: HashSet<String> = acceptable_words.iter().cloned().collect();
+    let mut sorted_words: Vec<String> = remaining_answers(table, guess_history, state_history)
+        .into_iter()
+        .map(|answer| table.answers()[answer].clone())
+        .collect();
+    sorted_words.sort();
+    sorted_words
+}
 
+/// Indices into `table.answers()` of the answers consistent with every guess.
+fn remaining_answers(
+    table: &PatternTable,
+    guess_history: &[String],
+    state_history: &[Feedback],
+) -> Vec<usize> {
+    let mut remaining: Vec<usize> = (0..table.answers().len()).collect();
     for (guess, state) in guess_history.iter().zip(state_history.iter()) {
-        let mut new_remaining_words = HashSet::new();
-        for word in &remaining_words {
-            if function::color_state(guess, word) == *state {
-                new_remaining_words.insert(word.clone());
-            }
+        let code = state.index();
+        match table.guess_index(guess) {
+            Some(guess) => remaining.retain(|&answer| table.pattern(guess, answer) == code),
+            // guesses outside the list are not in the table
+            None => remaining
+                .retain(|&answer| function::color_state(guess, &table.answers()[answer]) == *state),
         }
-        remaining_words = new_remaining_words;
     }
+    remaining
+}
 
-    let mut sorted_words: Vec<String> = remaining_words.into_iter().collect();
-    sorted_words.sort();
-    sorted_words
+/// The words that give `state` when guessed against `answer`; the reverse
+/// of [`find_remaining_words`], used to check shared result grids.
+pub fn find_possible_guesses(
+    acceptable_words: &[String],
+    answer: &str,
+    state: &Feedback,
+) -> Vec<String> {
+    let mut words: Vec<String> = acceptable_words
+        .iter()
+        .filter(|word| function::color_state(word, answer) == *state)
+        .cloned()
+        .collect();
+    words.sort();
+    words
 }
 
 pub fn print_remaining_words(
-    acceptable_words: &[String],
+    table: &PatternTable,
     guess_history: &[String],
-    state_history: &[[char; 5]],
+    state_history: &[Feedback],
 ) {
-    let sorted_words = find_remaining_words(acceptable_words, guess_history, state_history);
+    let sorted_words = find_remaining_words(table, guess_history, state_history);
     println!("-------------------");
     println!("Possible answers ({}):", sorted_words.len());
     if sorted_words.len() <= 50 {
@@ -57,80 +87,93 @@ pub fn print_remaining_words(
     }
 }
 
+/// Ranks guesses by how well they split the remaining candidates with
+/// `strategy`. Any guess of `table` may be recommended, even one that
+/// cannot be the answer.
 pub fn print_top_recommendations(
-    acceptable_words: &[String],
+    table: &PatternTable,
     guess_history: &[String],
-    state_history: &[[char; 5]],
+    state_history: &[Feedback],
+    strategy: StrategyKind,
 ) {
     println!("rec start");
-    let remaining_words = find_remaining_words(acceptable_words, guess_history, state_history);
+    let remaining_answers = remaining_answers(table, guess_history, state_history);
 
-    if remaining_words.len() <= 1 {
+    if remaining_answers.len() <= 1 {
         println!(
             "No recommendations needed. Remaining words: {}",
-            remaining_words.len()
+            remaining_answers.len()
         );
         return;
     }
 
-    let mut scores: Vec<(String, f64)> = Vec::new();
-
-    let search_set: Vec<String> = if remaining_words.len() <= 500 {
-        remaining_words.clone()
+    let search_set: Vec<usize> = if remaining_answers.len() <= 500 {
+        remaining_answers
+            .iter()
+            .filter_map(|&answer| table.guess_index(&table.answers()[answer]))
+            .collect()
     } else {
-        acceptable_words.to_vec()
+        (0..table.guesses().len()).collect()
     };
-
-    for guess in &search_set {
-        let mut partition_sizes: std::collections::HashMap<[char; 5], usize> =
-            std::collections::HashMap::new();
-        for answer in &remaining_words {
-            let state = function::color_state(guess, answer);
-            *partition_sizes.entry(state).or_insert(0) += 1;
-        }
-
-        let mut score = 0.0;
-        let total_size = remaining_words.len() as f64;
-        for size in partition_sizes.values() {
-            let p = *size as f64 / total_size;
-            if p > 0.0 {
-                score += p * p.log2();
-            }
-        }
-
-        scores.push((guess.clone(), -score));
-    }
-
-    scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    let strategy = strategy.strategy();
+    let scores = strategy.rank(table, &search_set, &remaining_answers);
 
     println!("-------------------");
     println!("Top 5 recommended words:");
     for (i, (word, score)) in scores.iter().take(5).enumerate() {
-        println!("{}. {} (Score: {:.2})", i + 1, word.to_uppercase(), score);
+        println!(
+            "{}. {} ({}: {:.2})",
+            i + 1,
+            word.to_uppercase(),
+            strategy.score_label(),
+            score
+        );
     }
 }
 
-pub fn solver_main() -> Result<(), Box<dyn std::error::Error>> {
+/// "Strategies: entropy, minimax, ...", for error messages.
+pub fn strategy_names() -> String {
+    let names: Vec<String> = StrategyKind::value_variants()
+        .iter()
+        .filter_map(|kind| kind.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    format!("Strategies: {}.", names.join(", "))
+}
+
+/// Limits scoring and table building to `threads` worker threads; must be
+/// called before either runs.
+pub fn set_thread_count(threads: usize) -> Result<(), Box<dyn std::error::Error>> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build_global()
+        .map_err(|e| format!("cannot start {threads} threads: {e}").into())
+}
+
+/// Interactive solver. Answers are looked for in `table.answers()` and
+/// guesses checked against `table.guesses()`; `strategy` ranks the
+/// recommendations unless `rec` names another one.
+pub fn solver_main(
+    table: &PatternTable,
+    strategy: StrategyKind,
+) -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", style("Welcome to Wordle Solver!").bold().green());
     println!("Please enter your guess and the resulting color state after each turn.");
     println!("Example: 'crane GGYRR' (G: Green, Y: Yellow, R: Red/Grey)");
     println!(
-        "Type 'rec' for a recommendation, 'left' to see remaining words, 'win' if you won, or 'quit' to exit."
+        "Type 'rec' (or 'rec minimax', 'rec expected-size', 'rec lookahead') for a recommendation, 'left' to see remaining words, 'win' if you won, or 'quit' to exit."
     );
 
-    let acceptable_words: Vec<String> = builtin_words::ACCEPTABLE
-        .iter()
-        .map(|&s| s.to_string())
-        .collect();
+    let word_length = match table.word_length() {
+        0 => function::DEFAULT_WORD_LENGTH,
+        length => length,
+    };
 
     let mut guess_history: Vec<String> = Vec::new();
-    let mut state_history: Vec<[char; 5]> = Vec::new();
+    let mut state_history: Vec<Feedback> = Vec::new();
 
     println!("\n--- Initial Recommendation ---");
-    println!(
-        "The best initial guess is: {}",
-        style("AEROS").bold().green()
-    );
+    print_top_recommendations(table, &[], &[], strategy);
 
     loop {
         println!("\n--- Enter your guess ---");
@@ -138,7 +181,9 @@ pub fn solver_main() -> Result<(), Box<dyn std::error::Error>> {
         io::stdout().flush()?;
 
         let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+        if io::stdin().read_line(&mut input)? == 0 {
+            break;
+        }
         let trimmed_input = input.trim().to_lowercase();
 
         match trimmed_input.as_str() {
@@ -151,11 +196,24 @@ pub fn solver_main() -> Result<(), Box<dyn std::error::Error>> {
                 break;
             }
             "rec" => {
-                print_top_recommendations(&acceptable_words, &guess_history, &state_history);
+                print_top_recommendations(table, &guess_history, &state_history, strategy);
+                continue;
+            }
+            rec if rec.starts_with("rec ") => {
+                let name = rec["rec ".len()..].trim();
+                match StrategyKind::from_str(name, true) {
+                    Ok(other) => {
+                        print_top_recommendations(table, &guess_history, &state_history, other)
+                    }
+                    Err(_) => println!(
+                        "{}",
+                        style(format!("Unknown strategy '{name}'. {}", strategy_names())).red()
+                    ),
+                }
                 continue;
             }
             "left" => {
-                print_remaining_words(&acceptable_words, &guess_history, &state_history);
+                print_remaining_words(table, &guess_history, &state_history);
                 continue;
             }
             _ => {
@@ -171,26 +229,35 @@ pub fn solver_main() -> Result<(), Box<dyn std::error::Error>> {
                 let guess = parts[0].to_lowercase();
                 let state_str = parts[1].to_uppercase();
 
-                if guess.len() != 5 || state_str.len() != 5 {
+                if guess.chars().count() != word_length || state_str.chars().count() != word_length
+                {
+                    println!(
+                        "{}",
+                        style(format!(
+                            "Guess and state must be {word_length} letters/characters long."
+                        ))
+                        .red()
+                    );
+                    continue;
+                }
+
+                if !guess.chars().all(|c| c.is_ascii_alphabetic()) {
                     println!(
                         "{}",
-                        style("Guess and state must be 5 letters/characters long.").red()
+                        style("The guess may only contain the letters a to z.").red()
                     );
                     continue;
                 }
 
-                let state: [char; 5] = match state_str.chars().collect::<Vec<char>>().try_into() {
+                let state: Feedback = match state_str.parse() {
                     Ok(s) => s,
-                    Err(_) => {
-                        println!(
-                            "{}",
-                            style("Invalid state format. Use only 'G', 'Y', 'R'.").red()
-                        );
+                    Err(e) => {
+                        println!("{}", style(format!("Invalid state format: {e}.")).red());
                         continue;
                     }
                 };
 
-                if !acceptable_words.contains(&guess) {
+                if table.guess_index(&guess).is_none() {
                     println!(
                         "{}",
                         style("Warning: This guess is not in the acceptable word list.").yellow()
@@ -200,8 +267,7 @@ pub fn solver_main() -> Result<(), Box<dyn std::error::Error>> {
                 guess_history.push(guess);
                 state_history.push(state);
 
-                let remaining =
-                    find_remaining_words(&acceptable_words, &guess_history, &state_history);
+                let remaining = find_remaining_words(table, &guess_history, &state_history);
 
                 if remaining.len() == 1 {
                     println!("\n{}", style("Found the answer! The word is:").green());
@@ -217,11 +283,16 @@ pub fn solver_main() -> Result<(), Box<dyn std::error::Error>> {
                         )
                         .red()
                     );
+                    if table.answers().len() < table.guesses().len() {
+                        println!(
+                            "If the answer is not in the final word list, run again with --all-answers."
+                        );
+                    }
                     break;
                 }
 
                 println!("\n{} possible words remain.", remaining.len());
-                print_top_recommendations(&acceptable_words, &guess_history, &state_history);
+                print_top_recommendations(table, &guess_history, &state_history, strategy);
             }
         }
     }
@@ -0,0 +1,355 @@
+//! Game history in an SQLite database, one row per game and per guess.
+//!
+//! The schema version is kept in `PRAGMA user_version` and follows
+//! [`migrate::CURRENT_SCHEMA_VERSION`] so both backends move together.
+
+use crate::feedback::Feedback;
+use crate::function::{GameRecord, GameState, LOCK_TIMEOUT};
+use crate::migrate;
+use crate::storage::Storage;
+use rusqlite::{Connection, Transaction, TransactionBehavior, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::error::Error;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS games (
+    id          INTEGER PRIMARY KEY,
+    answer      TEXT NOT NULL,
+    max_guesses INTEGER NOT NULL,
+    puzzle      INTEGER,
+    difficulty  TEXT,
+    day         INTEGER,
+    seed        INTEGER,
+    word_list   TEXT,
+    started_at  TEXT,
+    finished_at TEXT,
+    outcome     TEXT,
+    in_progress INTEGER NOT NULL DEFAULT 0,
+    player      TEXT
+);
+CREATE TABLE IF NOT EXISTS guesses (
+    game_id  INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    word     TEXT NOT NULL,
+    feedback TEXT NOT NULL,
+    PRIMARY KEY (game_id, position)
+);
+";
+
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Opens the database at `path`, creating it and its tables if needed.
+    pub fn open(path: &str) -> Result<Self, Box<dyn Error>> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(LOCK_TIMEOUT)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        // readers do not block the writer, which matters with many players
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+
+        let version: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > migrate::CURRENT_SCHEMA_VERSION {
+            return Err(format!(
+                "database {path} has schema version {version}, this program supports up to {}",
+                migrate::CURRENT_SCHEMA_VERSION
+            )
+            .into());
+        }
+        // version 2 databases predate player profiles
+        if version == 2 {
+            conn.execute_batch("ALTER TABLE games ADD COLUMN player TEXT")?;
+        }
+        conn.execute_batch(SCHEMA)?;
+        // guesses used to be numbered from 0; like the export they count from 1
+        conn.execute_batch(
+            "BEGIN IMMEDIATE;
+             UPDATE guesses SET position = -position - 1
+                 WHERE game_id IN (SELECT game_id FROM guesses WHERE position = 0);
+             UPDATE guesses SET position = -position WHERE position < 0;
+             COMMIT;",
+        )?;
+        conn.pragma_update(None, "user_version", migrate::CURRENT_SCHEMA_VERSION)?;
+        Ok(SqliteStore { conn })
+    }
+
+    /// Starts a write transaction, taking the database lock right away so
+    /// concurrent writers wait instead of failing halfway.
+    fn write(&self) -> rusqlite::Result<Transaction<'_>> {
+        Transaction::new_unchecked(&self.conn, TransactionBehavior::Immediate)
+    }
+}
+
+impl Storage for SqliteStore {
+    fn load(&self) -> Result<Option<GameState>, Box<dyn Error>> {
+        let mut guesses: HashMap<i64, Vec<String>> = HashMap::new();
+        let mut stmt = self
+            .conn
+            .prepare("SELECT game_id, word FROM guesses ORDER BY game_id, position")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            guesses.entry(row.get(0)?).or_default().push(row.get(1)?);
+        }
+
+        let mut games = Vec::new();
+        let mut in_progress = Vec::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT id, answer, max_guesses, puzzle, difficulty, day, seed, word_list,
+                    started_at, finished_at, outcome, in_progress, player
+             FROM games ORDER BY id",
+        )?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get(0)?;
+            let record = GameRecord {
+                answer: row.get(1)?,
+                guesses: guesses.remove(&id).unwrap_or_default(),
+                max_guesses: row.get(2)?,
+                puzzle: row.get(3)?,
+                difficulty: from_text(row.get(4)?)?,
+                day: row.get(5)?,
+                // stored as the same 64 bits, SQLite integers are signed
+                seed: row.get::<_, Option<i64>>(6)?.map(|seed| seed as u64),
+                word_list: row.get(7)?,
+                started_at: row.get(8)?,
+                finished_at: row.get(9)?,
+                outcome: from_text(row.get(10)?)?,
+                player: row.get(12)?,
+            };
+            if row.get(11)? {
+                in_progress.push(record);
+            } else {
+                games.push(record);
+            }
+        }
+
+        if games.is_empty() && in_progress.is_empty() {
+            return Ok(None);
+        }
+        let mut state = GameState::new(games);
+        state.in_progress = in_progress;
+        Ok(Some(state))
+    }
+
+    fn append_game(&self, record: GameRecord) -> Result<GameState, Box<dyn Error>> {
+        let tx = self.write()?;
+        tx.execute(
+            "DELETE FROM games WHERE in_progress AND player IS ?1",
+            [&record.player],
+        )?;
+        insert_game(&tx, &record, false)?;
+        tx.commit()?;
+        Ok(self.load()?.unwrap_or_else(|| GameState::new(vec![record])))
+    }
+
+    fn save_in_progress(
+        &self,
+        player: Option<&str>,
+        record: Option<GameRecord>,
+    ) -> Result<(), Box<dyn Error>> {
+        let tx = self.write()?;
+        tx.execute(
+            "DELETE FROM games WHERE in_progress AND player IS ?1",
+            [player],
+        )?;
+        if let Some(record) = &record {
+            insert_game(&tx, record, true)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn insert_game(
+    tx: &Transaction<'_>,
+    record: &GameRecord,
+    in_progress: bool,
+) -> Result<(), Box<dyn Error>> {
+    tx.execute(
+        "INSERT INTO games (answer, max_guesses, puzzle, difficulty, day, seed, word_list,
+                            started_at, finished_at, outcome, in_progress, player)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            record.answer,
+            record.max_guesses,
+            record.puzzle,
+            to_text(&record.difficulty)?,
+            record.day,
+            record.seed.map(|seed| seed as i64),
+            record.word_list,
+            record.started_at,
+            record.finished_at,
+            to_text(&record.outcome)?,
+            in_progress,
+            record.player,
+        ],
+    )?;
+    let game_id = tx.last_insert_rowid();
+
+    let answer = record.answer.to_lowercase();
+    let mut stmt = tx.prepare(
+        "INSERT INTO guesses (game_id, position, word, feedback) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (position, guess) in record.guesses.iter().enumerate() {
+        // kept so guesses can be queried by colour without replaying the game
+        let feedback = Feedback::compute(&guess.to_lowercase(), &answer);
+        stmt.execute(params![game_id, position + 1, guess, feedback.to_string()])?;
+    }
+    Ok(())
+}
+
+/// Enum columns use the same names as the JSON state file.
+fn to_text<T: Serialize>(value: &Option<T>) -> Result<Option<String>, Box<dyn Error>> {
+    match value {
+        Some(value) => match serde_json::to_value(value)? {
+            serde_json::Value::String(text) => Ok(Some(text)),
+            other => Err(format!("cannot store {other} as text").into()),
+        },
+        None => Ok(None),
+    }
+}
+
+fn from_text<T: DeserializeOwned>(text: Option<String>) -> Result<Option<T>, Box<dyn Error>> {
+    text.map(|text| serde_json::from_value(serde_json::Value::String(text)))
+        .transpose()
+        .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Difficulty, GameStatus};
+    use assert_json_diff::assert_json_eq;
+    use chrono::Utc;
+
+    /// A fresh database path in the temp directory, removed when dropped.
+    struct TempDb(String);
+
+    impl TempDb {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("wordle-sqlite-{}-{name}.db", std::process::id()));
+            let db = TempDb(path.to_str().unwrap().to_string());
+            db.remove();
+            db
+        }
+
+        fn remove(&self) {
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = std::fs::remove_file(format!("{}{suffix}", self.0));
+            }
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            self.remove();
+        }
+    }
+
+    fn record(answer: &str, guesses: &[&str], player: Option<&str>) -> GameRecord {
+        GameRecord {
+            answer: answer.to_string(),
+            guesses: guesses.iter().map(|guess| guess.to_string()).collect(),
+            max_guesses: 6,
+            player: player.map(str::to_string),
+            ..GameRecord::default()
+        }
+    }
+
+    fn json(records: &[GameRecord]) -> serde_json::Value {
+        serde_json::to_value(records).unwrap()
+    }
+
+    #[test]
+    fn round_trips_games_and_games_in_progress() {
+        let db = TempDb::new("round-trip");
+        let store = SqliteStore::open(&db.0).unwrap();
+        assert!(store.load().unwrap().is_none());
+
+        let finished = GameRecord {
+            puzzle: Some(123),
+            difficulty: Some(Difficulty::Strict),
+            day: Some(4),
+            // does not fit an i64, SQLite keeps the same bits
+            seed: Some(u64::MAX - 1),
+            word_list: Some("builtin".to_string()),
+            started_at: Some(Utc::now()),
+            finished_at: Some(Utc::now()),
+            outcome: Some(GameStatus::Won),
+            ..record("TAPIR", &["CRANE", "TAPIR"], None)
+        };
+        let alice = record("HELLO", &["WORLD"], Some("alice"));
+        let default = record("CIGAR", &["CRANE", "CHAIR"], None);
+        store.append_game(finished.clone()).unwrap();
+        store
+            .save_in_progress(Some("alice"), Some(alice.clone()))
+            .unwrap();
+        store.save_in_progress(None, Some(default.clone())).unwrap();
+
+        let state = SqliteStore::open(&db.0).unwrap().load().unwrap().unwrap();
+        assert_json_eq!(json(&state.games), json(&[finished]));
+        assert_json_eq!(json(&state.in_progress), json(&[alice.clone(), default]));
+
+        // only the unfinished game of the player without a name is cleared
+        store.save_in_progress(None, None).unwrap();
+        let state = store.load().unwrap().unwrap();
+        assert_json_eq!(json(&state.in_progress), json(&[alice]));
+    }
+
+    #[test]
+    fn finishing_a_game_clears_only_that_players_game_in_progress() {
+        let db = TempDb::new("finish");
+        let store = SqliteStore::open(&db.0).unwrap();
+        let alice = record("HELLO", &["WORLD"], Some("alice"));
+        store
+            .save_in_progress(Some("alice"), Some(alice.clone()))
+            .unwrap();
+        store
+            .save_in_progress(None, Some(record("CIGAR", &[], None)))
+            .unwrap();
+
+        let state = store
+            .append_game(record("CIGAR", &["CIGAR"], None))
+            .unwrap();
+        assert_eq!(state.games.len(), 1);
+        assert_json_eq!(json(&state.in_progress), json(&[alice]));
+    }
+
+    #[test]
+    fn numbers_guesses_from_one() {
+        let db = TempDb::new("positions");
+        let store = SqliteStore::open(&db.0).unwrap();
+        store
+            .append_game(record("TAPIR", &["CRANE", "TAPIR"], None))
+            .unwrap();
+        let positions = |conn: &Connection| -> Vec<(i64, String)> {
+            let mut stmt = conn
+                .prepare("SELECT position, word FROM guesses ORDER BY position")
+                .unwrap();
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .unwrap()
+                .map(Result::unwrap)
+                .collect()
+        };
+        let expected = vec![(1, "CRANE".to_string()), (2, "TAPIR".to_string())];
+        assert_eq!(positions(&store.conn), expected);
+
+        // databases written before counted from 0
+        store
+            .conn
+            .execute_batch(
+                "UPDATE guesses SET position = -position;
+                 UPDATE guesses SET position = -position - 1;",
+            )
+            .unwrap();
+        drop(store);
+        let store = SqliteStore::open(&db.0).unwrap();
+        assert_eq!(positions(&store.conn), expected);
+        let state = store.load().unwrap().unwrap();
+        assert_eq!(state.games[0].guesses, ["CRANE", "TAPIR"]);
+    }
+}
@@ -0,0 +1,90 @@
+//! Where game history is kept: the JSON state file, or an SQLite database
+//! when built with the `sqlite` feature.
+
+use crate::function::{self, GameRecord, GameState};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// Persistent game history shared by the `play`, `stats` and `replay` commands.
+pub trait Storage {
+    /// Reads the whole history, or `None` if there is nothing stored yet.
+    fn load(&self) -> Result<Option<GameState>, Box<dyn Error>>;
+
+    /// Adds a finished game, clears its player's unfinished one and returns
+    /// the history including games saved concurrently by other sessions.
+    fn append_game(&self, record: GameRecord) -> Result<GameState, Box<dyn Error>>;
+
+    /// Saves (or with `None` clears) the game `player` has in progress.
+    fn save_in_progress(
+        &self,
+        player: Option<&str>,
+        record: Option<GameRecord>,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreKind {
+    Json,
+    Sqlite,
+}
+
+impl StoreKind {
+    /// SQLite for `.db`, `.sqlite` and `.sqlite3` files, JSON otherwise.
+    pub fn from_path(path: &str) -> Self {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("db" | "sqlite" | "sqlite3") => StoreKind::Sqlite,
+            _ => StoreKind::Json,
+        }
+    }
+}
+
+/// Opens the history at `path`, guessing the kind from the extension
+/// unless `kind` is given.
+pub fn open(path: &str, kind: Option<StoreKind>) -> Result<Box<dyn Storage>, Box<dyn Error>> {
+    match kind.unwrap_or_else(|| StoreKind::from_path(path)) {
+        StoreKind::Json => Ok(Box::new(JsonStore::new(path))),
+        #[cfg(feature = "sqlite")]
+        StoreKind::Sqlite => Ok(Box::new(crate::sqlite_store::SqliteStore::open(path)?)),
+        #[cfg(not(feature = "sqlite"))]
+        StoreKind::Sqlite => {
+            Err("SQLite storage is not available, rebuild with `--features sqlite`".into())
+        }
+    }
+}
+
+/// The JSON state file, see [`function::load_state`] and [`function::save_state`].
+pub struct JsonStore {
+    path: String,
+}
+
+impl JsonStore {
+    pub fn new(path: &str) -> Self {
+        JsonStore {
+            path: path.to_string(),
+        }
+    }
+}
+
+impl Storage for JsonStore {
+    fn load(&self) -> Result<Option<GameState>, Box<dyn Error>> {
+        function::load_state(&self.path)
+    }
+
+    fn append_game(&self, record: GameRecord) -> Result<GameState, Box<dyn Error>> {
+        function::append_game(&self.path, record)
+    }
+
+    fn save_in_progress(
+        &self,
+        player: Option<&str>,
+        record: Option<GameRecord>,
+    ) -> Result<(), Box<dyn Error>> {
+        function::save_in_progress(&self.path, player, record)
+    }
+}
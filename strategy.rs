@@ -0,0 +1,460 @@
+//! Ways to rank the solver's guesses, selectable with `--strategy` or
+//! `rec <strategy>`.
+//!
+//! Every ranking is computed in parallel and ordered best first, ties
+//! alphabetically, so it does not depend on the number of threads.
+
+use crate::feedback::Feedback;
+use crate::pattern::PatternTable;
+use clap::ValueEnum;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Ranks candidate guesses for the answers that are still possible.
+pub trait Strategy: Sync {
+    /// Shown next to each recommendation's score.
+    fn score_label(&self) -> &'static str;
+
+    /// Scores guesses of `search_set` (indices into `table.guesses()`) for
+    /// the `remaining` answers (indices into `table.answers()`), best first.
+    /// May leave out guesses it did not consider.
+    fn rank(
+        &self,
+        table: &PatternTable,
+        search_set: &[usize],
+        remaining: &[usize],
+    ) -> Vec<(String, f64)>;
+}
+
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum StrategyKind {
+    /// Most expected information (the default)
+    #[default]
+    Entropy,
+    /// Smallest worst-case number of remaining answers
+    Minimax,
+    /// Smallest expected number of remaining answers
+    ExpectedSize,
+    /// Fewest expected guesses, looking two guesses ahead (slower)
+    Lookahead,
+}
+
+impl StrategyKind {
+    pub fn strategy(self) -> &'static dyn Strategy {
+        match self {
+            StrategyKind::Entropy => &Entropy,
+            StrategyKind::Minimax => &Minimax,
+            StrategyKind::ExpectedSize => &ExpectedSize,
+            StrategyKind::Lookahead => &Lookahead,
+        }
+    }
+}
+
+/// Shannon entropy of the feedback, in bits.
+pub struct Entropy;
+
+impl Strategy for Entropy {
+    fn score_label(&self) -> &'static str {
+        "Score"
+    }
+
+    fn rank(
+        &self,
+        table: &PatternTable,
+        search_set: &[usize],
+        remaining: &[usize],
+    ) -> Vec<(String, f64)> {
+        rank_by_partition(table, search_set, remaining, false, |sizes, total| {
+            sizes
+                .iter()
+                .filter(|&&size| size > 0)
+                .map(|&size| {
+                    let p = size as f64 / total as f64;
+                    -p * p.log2()
+                })
+                .sum()
+        })
+    }
+}
+
+/// Size of the largest group of answers sharing a feedback.
+pub struct Minimax;
+
+impl Strategy for Minimax {
+    fn score_label(&self) -> &'static str {
+        "worst case"
+    }
+
+    fn rank(
+        &self,
+        table: &PatternTable,
+        search_set: &[usize],
+        remaining: &[usize],
+    ) -> Vec<(String, f64)> {
+        rank_by_partition(table, search_set, remaining, true, |sizes, _| {
+            sizes.iter().copied().max().unwrap_or(0) as f64
+        })
+    }
+}
+
+/// Number of answers expected to remain after the guess.
+pub struct ExpectedSize;
+
+impl Strategy for ExpectedSize {
+    fn score_label(&self) -> &'static str {
+        "expected left"
+    }
+
+    fn rank(
+        &self,
+        table: &PatternTable,
+        search_set: &[usize],
+        remaining: &[usize],
+    ) -> Vec<(String, f64)> {
+        rank_by_partition(table, search_set, remaining, true, |sizes, total| {
+            let squares: usize = sizes.iter().map(|&size| size * size).sum();
+            squares as f64 / total as f64
+        })
+    }
+}
+
+/// Expected number of guesses to solve, counting this one, when the best
+/// second guess is played after it and the rest is estimated.
+///
+/// Only the [`LOOKAHEAD_WIDTH`] best guesses by entropy are looked at,
+/// plus the remaining answers when there are few of them; the same
+/// shortlist and the answers left are tried as second guesses.
+pub struct Lookahead;
+
+/// First and second guesses the lookahead considers.
+pub const LOOKAHEAD_WIDTH: usize = 20;
+
+impl Strategy for Lookahead {
+    fn score_label(&self) -> &'static str {
+        "expected guesses"
+    }
+
+    fn rank(
+        &self,
+        table: &PatternTable,
+        search_set: &[usize],
+        remaining: &[usize],
+    ) -> Vec<(String, f64)> {
+        let mut shortlist: Vec<usize> = Entropy
+            .rank(table, search_set, remaining)
+            .iter()
+            .take(LOOKAHEAD_WIDTH)
+            .filter_map(|(word, _)| table.guess_index(word))
+            .collect();
+        if remaining.len() <= LOOKAHEAD_WIDTH {
+            shortlist.extend(
+                remaining
+                    .iter()
+                    .filter_map(|&answer| table.guess_index(&table.answers()[answer])),
+            );
+        }
+        shortlist.sort_unstable();
+        shortlist.dedup();
+
+        let mut scores: Vec<(String, f64)> = shortlist
+            .par_iter()
+            .map(|&guess| {
+                let score = expected_guesses(table, guess, remaining, &shortlist, 2);
+                (table.guesses()[guess].clone(), score)
+            })
+            .collect();
+        sort_scores(&mut scores, true);
+        scores
+    }
+}
+
+/// Expected guesses to find the answer among `answers` starting with
+/// `guess`, trying the best follow-up from `shortlist` for `depth - 1` more
+/// guesses before falling back to [`estimated_guesses`].
+fn expected_guesses(
+    table: &PatternTable,
+    guess: usize,
+    answers: &[usize],
+    shortlist: &[usize],
+    depth: u32,
+) -> f64 {
+    // ordered, so the sum below adds up the same way every time
+    let mut groups: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+    for &answer in answers {
+        if table.answers()[answer] != table.guesses()[guess] {
+            groups
+                .entry(table.pattern(guess, answer))
+                .or_default()
+                .push(answer);
+        }
+    }
+
+    let total = answers.len() as f64;
+    let mut expected = 1.0;
+    for group in groups.values() {
+        let rest = if depth <= 1 || group.len() <= 2 {
+            estimated_guesses(group.len())
+        } else {
+            shortlist
+                .iter()
+                .copied()
+                .chain(
+                    group
+                        .iter()
+                        .filter_map(|&answer| table.guess_index(&table.answers()[answer])),
+                )
+                .map(|next| expected_guesses(table, next, group, shortlist, depth - 1))
+                .fold(f64::INFINITY, f64::min)
+        };
+        expected += group.len() as f64 / total * rest;
+    }
+    expected
+}
+
+/// Rough number of guesses still needed with `count` answers left: exact
+/// for one or two, for more about one guess per sixfold reduction but
+/// never less than guessing the answers one by one could take on average.
+fn estimated_guesses(count: usize) -> f64 {
+    let count = count as f64;
+    let one_by_one = (2.0 * count - 1.0) / count;
+    one_by_one.max(1.0 + count.ln() / 6f64.ln())
+}
+
+/// Ranks guesses by a score computed from how many of the `remaining`
+/// answers fall in each feedback group.
+fn rank_by_partition(
+    table: &PatternTable,
+    search_set: &[usize],
+    remaining: &[usize],
+    lower_is_better: bool,
+    score: impl Fn(&[usize], usize) -> f64 + Sync,
+) -> Vec<(String, f64)> {
+    let pattern_count = Feedback::pattern_count(table.word_length());
+    let mut scores: Vec<(String, f64)> = search_set
+        .par_iter()
+        .map_init(
+            || vec![0usize; pattern_count],
+            |partition_sizes, &guess| {
+                partition_sizes.fill(0);
+                for &answer in remaining {
+                    partition_sizes[table.pattern(guess, answer) as usize] += 1;
+                }
+                (
+                    table.guesses()[guess].clone(),
+                    score(partition_sizes, remaining.len()),
+                )
+            },
+        )
+        .collect();
+    sort_scores(&mut scores, lower_is_better);
+    scores
+}
+
+fn sort_scores(scores: &mut [(String, f64)], lower_is_better: bool) {
+    scores.sort_by(|a, b| {
+        let order = a.1.total_cmp(&b.1);
+        let order = if lower_is_better {
+            order
+        } else {
+            order.reverse()
+        };
+        order.then_with(|| a.0.cmp(&b.0))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|word| word.to_string()).collect()
+    }
+
+    /// Answers B/C × A/E/I followed by the guesses AEI, which splits them
+    /// by vowel into three pairs, and BBB, which splits them in halves.
+    /// Each answer splits the others into its own, two pairs and a single.
+    fn table() -> PatternTable {
+        let answers = words(&["bad", "bed", "bid", "cad", "ced", "cid"]);
+        let mut guesses = words(&["bbb", "aei"]);
+        guesses.extend(answers.iter().cloned());
+        PatternTable::build(&guesses, &answers)
+    }
+
+    fn rank(kind: StrategyKind, table: &PatternTable) -> Vec<(String, f64)> {
+        let search_set: Vec<usize> = (0..table.guesses().len()).collect();
+        let remaining: Vec<usize> = (0..table.answers().len()).collect();
+        kind.strategy().rank(table, &search_set, &remaining)
+    }
+
+    fn assert_ranking(kind: StrategyKind, expected: &[(&str, f64)]) {
+        let ranking = rank(kind, &table());
+        let order: Vec<&str> = ranking.iter().map(|(word, _)| word.as_str()).collect();
+        let expected_order: Vec<&str> = expected.iter().map(|&(word, _)| word).collect();
+        assert_eq!(order, expected_order, "{kind:?}");
+        for ((word, score), (_, expected_score)) in ranking.iter().zip(expected) {
+            assert!(
+                (score - expected_score).abs() < 1e-9,
+                "{kind:?} {word}: {score} != {expected_score}"
+            );
+        }
+    }
+
+    #[test]
+    fn entropy_prefers_the_answers() {
+        // two pairs and two singles: 2/3·log2(3) + 1/3·log2(6)
+        let answer = 2.0 / 3.0 * 3f64.log2() + 6f64.log2() / 3.0;
+        assert_ranking(
+            StrategyKind::Entropy,
+            &[
+                ("bad", answer),
+                ("bed", answer),
+                ("bid", answer),
+                ("cad", answer),
+                ("ced", answer),
+                ("cid", answer),
+                ("aei", 3f64.log2()),
+                ("bbb", 1.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn minimax_ties_break_alphabetically() {
+        assert_ranking(
+            StrategyKind::Minimax,
+            &[
+                ("aei", 2.0),
+                ("bad", 2.0),
+                ("bed", 2.0),
+                ("bid", 2.0),
+                ("cad", 2.0),
+                ("ced", 2.0),
+                ("cid", 2.0),
+                ("bbb", 3.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn expected_size_counts_answers_left() {
+        assert_ranking(
+            StrategyKind::ExpectedSize,
+            &[
+                ("bad", 10.0 / 6.0),
+                ("bed", 10.0 / 6.0),
+                ("bid", 10.0 / 6.0),
+                ("cad", 10.0 / 6.0),
+                ("ced", 10.0 / 6.0),
+                ("cid", 10.0 / 6.0),
+                ("aei", 2.0),
+                ("bbb", 3.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn lookahead_counts_expected_guesses() {
+        // an answer wins 1 time in 6 and leaves a single or a pair, which
+        // take 1 and 1.5 more guesses
+        let answer = 1.0 + (1.0 + 2.0 * 1.5 + 2.0 * 1.5) / 6.0;
+        assert_ranking(
+            StrategyKind::Lookahead,
+            &[
+                ("bad", answer),
+                ("bed", answer),
+                ("bid", answer),
+                ("cad", answer),
+                ("ced", answer),
+                ("cid", answer),
+                // three pairs
+                ("aei", 2.5),
+                // halves of three, each solved by AEI in one more guess
+                ("bbb", 3.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn lookahead_beats_the_estimate_one_step_later() {
+        let table = table();
+        let all: Vec<usize> = (0..table.answers().len()).collect();
+        let shortlist: Vec<usize> = (0..table.guesses().len()).collect();
+        let bbb = table.guess_index("bbb").unwrap();
+        // halves of three are estimated at 5/3 guesses, but take 2
+        let estimated = expected_guesses(&table, bbb, &all, &shortlist, 1);
+        assert!((estimated - (1.0 + 5.0 / 3.0)).abs() < 1e-9, "{estimated}");
+        let looked_ahead = expected_guesses(&table, bbb, &all, &shortlist, 2);
+        assert!((looked_ahead - 3.0).abs() < 1e-9, "{looked_ahead}");
+
+        // guessing the only answer left takes one guess
+        let bad = table.guess_index("bad").unwrap();
+        let answer = table
+            .answers()
+            .iter()
+            .position(|word| word == "bad")
+            .unwrap();
+        assert_eq!(expected_guesses(&table, bad, &[answer], &shortlist, 2), 1.0);
+    }
+
+    #[test]
+    fn estimates_remaining_guesses() {
+        assert_eq!(estimated_guesses(1), 1.0);
+        assert_eq!(estimated_guesses(2), 1.5);
+        assert!((estimated_guesses(3) - 5.0 / 3.0).abs() < 1e-9);
+        let many = estimated_guesses(216);
+        assert!((many - 4.0).abs() < 1e-9, "{many}");
+    }
+
+    #[test]
+    fn sorts_ties_alphabetically_in_both_directions() {
+        let scores = || {
+            vec![
+                ("b".to_string(), 1.0),
+                ("c".to_string(), 2.0),
+                ("a".to_string(), 1.0),
+                ("d".to_string(), 2.0),
+            ]
+        };
+        let order = |scores: Vec<(String, f64)>| -> Vec<String> {
+            scores.into_iter().map(|(word, _)| word).collect()
+        };
+        let mut lower = scores();
+        sort_scores(&mut lower, true);
+        assert_eq!(order(lower), ["a", "b", "c", "d"]);
+        let mut higher = scores();
+        sort_scores(&mut higher, false);
+        assert_eq!(order(higher), ["c", "d", "a", "b"]);
+    }
+
+    #[test]
+    fn ranking_does_not_depend_on_threads() {
+        let letters = ['a', 'b', 'c', 'd', 'e', 'f', 'g'];
+        let mut all = Vec::new();
+        for &x in &letters {
+            for &y in &letters {
+                for &z in &letters {
+                    all.push(format!("{x}{y}{z}"));
+                }
+            }
+        }
+        let table = PatternTable::build(&all, &all[..120]);
+        let ranking_with = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                StrategyKind::value_variants()
+                    .iter()
+                    .map(|&kind| rank(kind, &table))
+                    .collect::<Vec<_>>()
+            })
+        };
+        let single = ranking_with(1);
+        assert_eq!(single, ranking_with(4));
+        assert_eq!(single, ranking_with(7));
+    }
+}